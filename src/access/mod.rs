@@ -0,0 +1,228 @@
+use crate::data::accessmap::AccessMap;
+use crate::ronn_utils::responder::CommandResponse;
+use serde::{Deserialize, Serialize};
+use serenity::model::channel::Message;
+use serenity::model::guild::Member;
+use serenity::model::id::{GuildId, RoleId, UserId};
+use serenity::model::Permissions;
+use serenity::prelude::Context;
+use std::collections::HashMap;
+use std::fmt;
+use tracing::warn;
+
+pub const STORE_KEY: &str = "access";
+/// The command that changes these rules, never gated so a guild cannot lock itself out.
+pub const SETTINGS_COMMAND: &str = "access";
+
+/// Who may run a command and where.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandRule {
+    pub disabled: bool,
+    /// The member needs at least one of these roles.
+    pub roles: Vec<u64>,
+    /// Raw `Permissions` bits the member needs, all of them.
+    pub permissions: u64,
+    /// When not empty, the command only works in these channels.
+    pub allowed_channels: Vec<u64>,
+    pub denied_channels: Vec<u64>,
+}
+
+/// A guild's rules. Channel lists apply to every command, `commands` add to them.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildAccess {
+    pub allowed_channels: Vec<u64>,
+    pub denied_channels: Vec<u64>,
+    pub commands: HashMap<String, CommandRule>,
+}
+
+/// Guild id to its rules.
+pub type AccessRules = HashMap<u64, GuildAccess>;
+
+/// Why a command was refused, shown to the member as is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Denied {
+    Disabled(String),
+    Channel(String),
+    MissingRole(String, Vec<u64>),
+    MissingPermissions(String, Permissions),
+}
+
+impl fmt::Display for Denied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Denied::Disabled(command) => write!(f, "`{}` is disabled in this server", command),
+            Denied::Channel(command) => write!(f, "`{}` cannot be used in this channel", command),
+            Denied::MissingRole(command, roles) => write!(
+                f,
+                "You need one of these roles to use `{}`: {}",
+                command,
+                roles
+                    .iter()
+                    .map(|role| format!("<@&{}>", role))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Denied::MissingPermissions(command, permissions) => write!(
+                f,
+                "You need the {} permission to use `{}`",
+                permissions, command
+            ),
+        }
+    }
+}
+
+/// The member running a command, only looked up when a rule needs it.
+#[derive(Debug, Clone, Default)]
+pub struct Caller {
+    pub roles: Vec<u64>,
+    pub permissions: Permissions,
+}
+
+impl Caller {
+    /// From an interaction member, which comes with its permissions.
+    pub fn from_member(member: &Member) -> Self {
+        Self {
+            roles: member.roles.iter().map(|role| role.0).collect(),
+            permissions: member.permissions.unwrap_or_default(),
+        }
+    }
+}
+
+/// Rules that apply before a guild changes anything.
+pub fn default_rule(command: &str) -> CommandRule {
+    let permissions = match command {
+        "reactionroles" => Permissions::MANAGE_ROLES,
+        "steal" => Permissions::MANAGE_EMOJIS_AND_STICKERS,
+        _ => Permissions::empty(),
+    };
+    CommandRule {
+        permissions: permissions.bits(),
+        ..Default::default()
+    }
+}
+
+pub fn rule_for(access: Option<&GuildAccess>, command: &str) -> CommandRule {
+    access
+        .and_then(|access| access.commands.get(command))
+        .cloned()
+        .unwrap_or_else(|| default_rule(command))
+}
+
+impl CommandRule {
+    /// Whether checking this rule needs the member's roles and permissions.
+    pub fn needs_caller(&self) -> bool {
+        !self.roles.is_empty() || self.permissions != 0
+    }
+}
+
+/// Checks `command` in `channel_id`. Administrators skip role, permission and channel
+/// checks but not a disabled command.
+pub fn check(
+    access: Option<&GuildAccess>,
+    command: &str,
+    channel_id: u64,
+    caller: Option<&Caller>,
+) -> Result<(), Denied> {
+    let command = command.to_lowercase();
+    if command == SETTINGS_COMMAND {
+        return Ok(());
+    }
+    let rule = rule_for(access, &command);
+    if rule.disabled {
+        return Err(Denied::Disabled(command));
+    }
+    if caller.is_some_and(|caller| caller.permissions.administrator()) {
+        return Ok(());
+    }
+    let global = access.cloned().unwrap_or_default();
+    let denied =
+        global.denied_channels.contains(&channel_id) || rule.denied_channels.contains(&channel_id);
+    let not_allowed = [&global.allowed_channels, &rule.allowed_channels]
+        .iter()
+        .any(|allowed| !allowed.is_empty() && !allowed.contains(&channel_id));
+    if denied || not_allowed {
+        return Err(Denied::Channel(command));
+    }
+    if !rule.needs_caller() {
+        return Ok(());
+    }
+    let caller = caller.cloned().unwrap_or_default();
+    if !rule.roles.is_empty() && !rule.roles.iter().any(|role| caller.roles.contains(role)) {
+        return Err(Denied::MissingRole(command, rule.roles));
+    }
+    let needed = Permissions::from_bits_truncate(rule.permissions);
+    if !caller.permissions.contains(needed) {
+        return Err(Denied::MissingPermissions(
+            command,
+            needed - caller.permissions,
+        ));
+    }
+    Ok(())
+}
+
+/// Base guild permissions of a member, from the roles since there is no cache.
+pub async fn fetch_caller(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> serenity::Result<Caller> {
+    let guild = guild_id.to_partial_guild(&ctx.http).await?;
+    let member = guild_id.member(&ctx.http, user_id).await?;
+    let roles: Vec<u64> = member.roles.iter().map(|role| role.0).collect();
+    let permissions = if guild.owner_id == user_id {
+        Permissions::all()
+    } else {
+        guild
+            .roles
+            .iter()
+            .filter(|(id, _)| **id == RoleId(guild_id.0) || member.roles.contains(id))
+            .fold(Permissions::empty(), |all, (_, role)| {
+                all | role.permissions
+            })
+    };
+    Ok(Caller { roles, permissions })
+}
+
+/// Checks a prefix command, replying to the member when it is refused.
+/// Returns whether the command may run.
+pub async fn allow_message(ctx: &Context, msg: &Message, command: &str) -> bool {
+    let Some(guild_id) = msg.guild_id else {
+        return true;
+    };
+    let rules = ctx
+        .data
+        .read()
+        .await
+        .get::<AccessMap>()
+        .expect("Expected AccessMap in TypeMap.")
+        .clone();
+    let access = rules.read().await.get(&guild_id.0).cloned();
+    // The member is only looked up when the command would be refused without them,
+    // as administrators skip role, permission and channel checks
+    let caller = match check(access.as_ref(), command, msg.channel_id.0, None) {
+        Ok(()) => return true,
+        Err(Denied::Disabled(_)) => None,
+        Err(_) => match fetch_caller(ctx, guild_id, msg.author.id).await {
+            Ok(caller) => Some(caller),
+            Err(why) => {
+                warn!(
+                    "Could not look up {} for access checks: {}",
+                    msg.author.id, why
+                );
+                None
+            }
+        },
+    };
+    match check(access.as_ref(), command, msg.channel_id.0, caller.as_ref()) {
+        Ok(()) => true,
+        Err(denied) => {
+            let response = CommandResponse::text(denied.to_string());
+            if let Err(why) = response.send(&ctx.http, msg.channel_id).await {
+                warn!("Could not explain access denial: {}", why);
+            }
+            false
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use crate::data::analyticsmap::AnalyticsMap;
+use crate::emojistats::day;
+use crate::ronn_utils::responder::ResponseEmbed;
+use crate::ronn_utils::store::{persist, Dirty};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::model::channel::Message;
+use serenity::prelude::{RwLock, TypeMap};
+use serenity::utils::Colour;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const STORE_KEY: &str = "command_usage";
+/// Daily counters older than this are dropped.
+pub const RETENTION_DAYS: i64 = 90;
+/// How many commands or users a report lists.
+const TOP: usize = 15;
+
+/// Counters changed since the last save.
+static CHANGES: Dirty = Dirty::new();
+
+/// One run of a prefix, custom or slash command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub command: String,
+    pub guild_id: Option<u64>,
+    pub channel_id: u64,
+    pub user_id: u64,
+    pub at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub success: bool,
+}
+
+impl Invocation {
+    /// A prefix or custom command, timed from when the bot `received` it.
+    pub fn from_message(msg: &Message, command: &str, received: Instant, success: bool) -> Self {
+        Self {
+            command: command.to_lowercase(),
+            guild_id: msg.guild_id.map(|id| id.0),
+            channel_id: msg.channel_id.0,
+            user_id: msg.author.id.0,
+            at: Utc::now(),
+            duration_ms: received.elapsed().as_millis() as u64,
+            success,
+        }
+    }
+}
+
+/// Runs of one command by one member in one channel on one day.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub uses: u64,
+    pub errors: u64,
+    pub total_ms: u64,
+}
+
+impl Usage {
+    fn add(&mut self, other: &Usage) {
+        self.uses += other.uses;
+        self.errors += other.errors;
+        self.total_ms += other.total_ms;
+    }
+}
+
+/// Member id to their usage.
+pub type MemberUsage = HashMap<u64, Usage>;
+/// Command name to the channels it ran in, each with the members who ran it there.
+pub type CommandUsage = HashMap<String, HashMap<u64, MemberUsage>>;
+/// Day (days since the epoch) to the commands run that day.
+pub type GuildUsage = BTreeMap<i64, CommandUsage>;
+/// Guild id to its daily command usage.
+pub type CommandStats = HashMap<u64, GuildUsage>;
+
+/// Periods `/stats` can report on.
+pub const PERIODS: &[(&str, Option<i64>)] = &[
+    ("day", Some(1)),
+    ("week", Some(7)),
+    ("month", Some(30)),
+    ("all", None),
+];
+
+/// Counts a guild invocation on the day it ran, direct messages are not reported on.
+pub fn record(stats: &mut CommandStats, invocation: Invocation) {
+    let Some(guild_id) = invocation.guild_id else {
+        return;
+    };
+    let usage = stats
+        .entry(guild_id)
+        .or_default()
+        .entry(day(invocation.at))
+        .or_default()
+        .entry(invocation.command)
+        .or_default()
+        .entry(invocation.channel_id)
+        .or_default()
+        .entry(invocation.user_id)
+        .or_default();
+    usage.uses += 1;
+    usage.total_ms += invocation.duration_ms;
+    if !invocation.success {
+        usage.errors += 1;
+    }
+}
+
+/// Drops days past the retention window, then guilds with no days left.
+pub fn prune(stats: &mut CommandStats, now: DateTime<Utc>) {
+    let oldest = day(now) - RETENTION_DAYS;
+    for days in stats.values_mut() {
+        days.retain(|day, _| *day > oldest);
+    }
+    stats.retain(|_, days| !days.is_empty());
+}
+
+fn period_name(days: Option<i64>) -> String {
+    match days {
+        Some(1) => "the last day".to_string(),
+        Some(days) => format!("the last {} days", days),
+        None => format!("the last {} days", RETENTION_DAYS),
+    }
+}
+
+/// Every (command, member, usage) counter of the guild within the last `days` days.
+fn in_period(
+    stats: &CommandStats,
+    guild_id: u64,
+    days: Option<i64>,
+    now: DateTime<Utc>,
+) -> impl Iterator<Item = (&str, u64, &Usage)> {
+    let since = days.map_or(i64::MIN, |days| day(now) - days + 1);
+    stats
+        .get(&guild_id)
+        .into_iter()
+        .flat_map(move |guild| guild.range(since..))
+        .flat_map(|(_, commands)| commands.iter())
+        .flat_map(|(command, channels)| {
+            channels.values().flat_map(move |users| {
+                users
+                    .iter()
+                    .map(move |(user_id, usage)| (command.as_str(), *user_id, usage))
+            })
+        })
+}
+
+/// Uses, failures and average duration per command, most used first.
+pub fn command_report(
+    stats: &CommandStats,
+    guild_id: u64,
+    days: Option<i64>,
+    now: DateTime<Utc>,
+) -> ResponseEmbed {
+    let mut usage: HashMap<&str, Usage> = HashMap::new();
+    for (command, _, counted) in in_period(stats, guild_id, days, now) {
+        usage.entry(command).or_default().add(counted);
+    }
+    let mut embed = ResponseEmbed {
+        title: Some(format!("Command use in {}", period_name(days))),
+        colour: Some(Colour::BLURPLE),
+        ..Default::default()
+    };
+    if usage.is_empty() {
+        embed.description = Some("No commands were used".to_string());
+        return embed;
+    }
+    let mut sorted: Vec<(&str, Usage)> = usage.into_iter().collect();
+    sorted.sort_by(|(a, a_usage), (b, b_usage)| b_usage.uses.cmp(&a_usage.uses).then(a.cmp(b)));
+    let lines: Vec<String> = sorted
+        .iter()
+        .take(TOP)
+        .map(|(command, usage)| {
+            format!(
+                "`{}` {} uses, {} failed, avg {}ms",
+                command,
+                usage.uses,
+                usage.errors,
+                usage.total_ms / usage.uses
+            )
+        })
+        .collect();
+    embed.description = Some(lines.join("\n"));
+
+    let mut failing: Vec<&(&str, Usage)> = sorted.iter().filter(|(_, u)| u.errors > 0).collect();
+    failing
+        .sort_by(|(a, a_usage), (b, b_usage)| b_usage.errors.cmp(&a_usage.errors).then(a.cmp(b)));
+    if !failing.is_empty() {
+        let errors: Vec<String> = failing
+            .iter()
+            .take(5)
+            .map(|(command, usage)| {
+                format!(
+                    "`{}` {} of {} ({}%)",
+                    command,
+                    usage.errors,
+                    usage.uses,
+                    usage.errors * 100 / usage.uses
+                )
+            })
+            .collect();
+        embed
+            .fields
+            .push(("Most errors".to_string(), errors.join("\n"), false));
+    }
+    let total: u64 = sorted.iter().map(|(_, usage)| usage.uses).sum();
+    embed.footer = Some(format!("{} commands run", total));
+    embed
+}
+
+/// Members who ran the most commands, with their favourite one.
+pub fn user_report(
+    stats: &CommandStats,
+    guild_id: u64,
+    days: Option<i64>,
+    now: DateTime<Utc>,
+) -> ResponseEmbed {
+    let mut users: HashMap<u64, HashMap<&str, u64>> = HashMap::new();
+    for (command, user_id, usage) in in_period(stats, guild_id, days, now) {
+        *users
+            .entry(user_id)
+            .or_default()
+            .entry(command)
+            .or_default() += usage.uses;
+    }
+    let mut embed = ResponseEmbed {
+        title: Some(format!("Most active members in {}", period_name(days))),
+        colour: Some(Colour::BLURPLE),
+        ..Default::default()
+    };
+    if users.is_empty() {
+        embed.description = Some("No commands were used".to_string());
+        return embed;
+    }
+    let mut sorted: Vec<(u64, u64, &str)> = users
+        .iter()
+        .map(|(user_id, commands)| {
+            let total = commands.values().sum();
+            let favourite = commands
+                .iter()
+                .max_by(|(a, a_uses), (b, b_uses)| a_uses.cmp(b_uses).then(b.cmp(a)))
+                .map(|(command, _)| *command)
+                .unwrap_or_default();
+            (*user_id, total, favourite)
+        })
+        .collect();
+    sorted.sort_by(|(a, a_total, _), (b, b_total, _)| b_total.cmp(a_total).then(a.cmp(b)));
+    let lines: Vec<String> = sorted
+        .iter()
+        .take(TOP)
+        .enumerate()
+        .map(|(rank, (user_id, total, favourite))| {
+            format!(
+                "{}. <@{}> {} commands, mostly `{}`",
+                rank + 1,
+                user_id,
+                total,
+                favourite
+            )
+        })
+        .collect();
+    embed.description = Some(lines.join("\n"));
+    embed.footer = Some(format!("{} members used commands", sorted.len()));
+    embed
+}
+
+/// Records an invocation from a hook, saving happens in the background.
+pub async fn track(data: &Arc<RwLock<TypeMap>>, invocation: Invocation) {
+    let stats = data
+        .read()
+        .await
+        .get::<AnalyticsMap>()
+        .expect("Expected AnalyticsMap in TypeMap.")
+        .clone();
+    record(&mut *stats.write().await, invocation);
+    CHANGES.mark();
+}
+
+/// Spawns the loop that prunes and saves changed counters every minute. Only the first
+/// call starts it.
+pub fn start(data: Arc<RwLock<TypeMap>>) {
+    CHANGES.start("Command stats", Duration::from_secs(60), move || {
+        let data = data.clone();
+        async move {
+            let stats = data
+                .read()
+                .await
+                .get::<AnalyticsMap>()
+                .expect("Expected AnalyticsMap in TypeMap.")
+                .clone();
+            prune(&mut *stats.write().await, Utc::now());
+            persist::<AnalyticsMap, _>(&data, STORE_KEY).await
+        }
+    });
+}
@@ -0,0 +1,24 @@
+//! Runs the bot without Shuttle, configured by flags or environment variables.
+use ronnbot_rs::bot::{self, BotOptions, USAGE};
+use ronnbot_rs::ronn_utils::logging::{self, LogFormat};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let flags = bot::parse_flags(args)?;
+    let lookup = |key: &str| flags.get(key).cloned().or_else(|| std::env::var(key).ok());
+    logging::init(
+        lookup("LOG_LEVEL").as_deref(),
+        lookup("LOG_FORMAT")
+            .map(|format| LogFormat::parse(&format))
+            .unwrap_or_default(),
+    );
+    let options = BotOptions::from_lookup(lookup)?;
+    let mut client = bot::build_client(options).await?;
+    client.start().await?;
+    Ok(())
+}
@@ -0,0 +1,736 @@
+use crate::analytics::{self, Invocation};
+use crate::commands::admin::*;
+use crate::commands::assets::*;
+use crate::commands::floor::*;
+use crate::commands::help::*;
+use crate::commands::meta::*;
+use crate::commands::reactionroles::*;
+use crate::commands::steal::*;
+use crate::cooldowns::Cooldowns;
+use crate::customcommands::Dispatched;
+use crate::data::botmap::BotMap;
+use crate::data::{
+    accessmap::AccessMap,
+    analyticsmap::AnalyticsMap,
+    apiclientmap::ApiClientMap,
+    commandtimermap::CommandTimerMap,
+    config::{Config, Credentials},
+    configpathmap::ConfigPathMap,
+    cooldownmap::CooldownMap,
+    credentialsmap::CredentialsMap,
+    customcommandmap::CustomCommandMap,
+    digestmap::DigestMap,
+    emojistatsmap::EmojiStatsMap,
+    greetingmap::GreetingMap,
+    guildemojimap::GuildEmojiMap,
+    marketdatamap::MarketDataMap,
+    messagemap::MessageMap,
+    ownermap::OwnerMap,
+    pendingrolemap::PendingRoleMap,
+    pricehistorymap::PriceHistoryMap,
+    reactionmap::ReactionMap,
+    settingsmap::SettingsMap,
+    shardmanagermap::ShardManagerMap,
+    storemap::StoreMap,
+};
+use crate::providers::{
+    dexscreener::DexScreener, geckoterminal::GeckoTerminal, opensea::OpenSea, reservoir::Reservoir,
+    MarketData, NftFloorProvider,
+};
+use crate::ronn_utils::apiclient::ApiClient;
+use crate::ronn_utils::assets::Assets;
+use crate::ronn_utils::logging;
+use crate::ronn_utils::responder::CommandResponse;
+use crate::ronn_utils::store::Store;
+use crate::ronn_utils::utils;
+use crate::{
+    access, cooldowns, customcommands, emojistats, greetings, metrics, roles, scheduler, settings,
+    slashcommands,
+};
+use anyhow::{anyhow, Context as _};
+use chrono::Utc;
+use serenity::async_trait;
+use serenity::framework::standard::macros::{group, hook};
+use serenity::framework::standard::{CommandResult, DispatchError};
+use serenity::framework::StandardFramework;
+use serenity::http::Http;
+use serenity::model::application::command::Command;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::Interaction;
+use serenity::model::application::ApplicationFlags;
+use serenity::model::channel::Message;
+use serenity::model::channel::{Reaction, ReactionType};
+use serenity::model::event::ResumedEvent;
+use serenity::model::gateway::Ready;
+use serenity::model::guild::{Emoji, Guild, Member};
+use serenity::model::id::{ChannelId, EmojiId, GuildId, MessageId};
+use serenity::model::user::User;
+use serenity::prelude::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, debug_span, error, info, trace, warn, Instrument, Span};
+struct Bot;
+#[async_trait]
+impl EventHandler for Bot {
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::ApplicationCommand(command) => {
+                let span = logging::command_span(
+                    "slash",
+                    &command.data.name,
+                    command.guild_id,
+                    command.user.id,
+                );
+                let started = Instant::now();
+                async {
+                    trace!(?command, "Received command interaction");
+                    let outcome = run_command(&ctx, &command).await;
+                    let duration_ms = started.elapsed().as_millis() as u64;
+                    info!(latency_ms = duration_ms, outcome, "Slash command finished");
+                    metrics::command("slash", &command.data.name, outcome);
+                    if matches!(outcome, "ok" | "error") {
+                        let invocation = Invocation {
+                            command: command.data.name.clone(),
+                            guild_id: command.guild_id.map(|id| id.0),
+                            channel_id: command.channel_id.0,
+                            user_id: command.user.id.0,
+                            at: Utc::now(),
+                            duration_ms,
+                            success: outcome == "ok",
+                        };
+                        analytics::track(&ctx.data, invocation).await;
+                    }
+                }
+                .instrument(span)
+                .await;
+            }
+            Interaction::MessageComponent(component) => {
+                if let Err(why) =
+                    slashcommands::help::run_button(&ctx, &component, &[&GENERAL_GROUP]).await
+                {
+                    warn!(custom_id = %component.data.custom_id, "Cannot turn the help page: {}", why);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        info!(user = %ready.user.name, "Connected");
+        for registered in slashcommands::registry() {
+            match Command::create_global_application_command(&ctx.http, |command| {
+                *command = registered;
+                command
+            })
+            .await
+            {
+                Ok(command) => debug!(name = %command.name, "Registered slash command"),
+                Err(why) => error!("Could not register a slash command: {}", why),
+            }
+        }
+
+        scheduler::start(ctx.http.clone(), ctx.data.clone());
+        greetings::start(ctx.http.clone(), ctx.data.clone());
+        emojistats::start(ctx.data.clone());
+        analytics::start(ctx.data.clone());
+        slashcommands::coin::start(ctx.data.clone());
+        customcommands::start(ctx.data.clone());
+    }
+
+    async fn resume(&self, _: Context, _: ResumedEvent) {
+        info!("Resumed");
+    }
+    async fn message(&self, ctx: Context, msg: Message) {
+        if let (Some(guild_id), false) = (msg.guild_id, msg.author.bot) {
+            let emoji_ids = utils::custom_emojis(&msg.content)
+                .into_iter()
+                .map(|emoji| emoji.id.0)
+                .collect();
+            emojistats::track(&ctx.data, guild_id.0, emoji_ids)
+                .instrument(debug_span!("message", guild = guild_id.0))
+                .await;
+        }
+    }
+
+    async fn guild_create(&self, ctx: Context, guild: Guild) {
+        emojistats::set_guild_emojis(&ctx.data, guild.id.0, guild.emojis.keys().map(|id| id.0))
+            .await;
+    }
+
+    async fn guild_emojis_update(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        current_state: HashMap<EmojiId, Emoji>,
+    ) {
+        emojistats::set_guild_emojis(&ctx.data, guild_id.0, current_state.keys().map(|id| id.0))
+            .await;
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let span = reaction_span("reaction_add", &reaction);
+        async {
+            if let (Some(guild_id), ReactionType::Custom { id, .. }) =
+                (reaction.guild_id, &reaction.emoji)
+            {
+                emojistats::track(&ctx.data, guild_id.0, vec![id.0]).await;
+            }
+            handle_reaction(ctx, reaction, true).await;
+        }
+        .instrument(span)
+        .await
+    }
+    async fn reaction_remove(&self, ctx: Context, reaction: Reaction) {
+        let span = reaction_span("reaction_remove", &reaction);
+        handle_reaction(ctx, reaction, false).instrument(span).await;
+    }
+
+    async fn guild_member_addition(&self, ctx: Context, new_member: Member) {
+        if new_member.user.bot {
+            return;
+        }
+        let span = member_span("member_add", new_member.guild_id, &new_member.user);
+        greetings::welcome(&ctx, &new_member).instrument(span).await;
+    }
+
+    async fn guild_member_removal(&self, ctx: Context, guild_id: GuildId, user: User) {
+        if user.bot {
+            return;
+        }
+        let span = member_span("member_remove", guild_id, &user);
+        greetings::farewell(&ctx, guild_id, &user)
+            .instrument(span)
+            .await;
+    }
+}
+/// Checks and runs a slash command, returning the outcome for the log.
+async fn run_command(ctx: &Context, command: &ApplicationCommandInteraction) -> &'static str {
+    if let Err(denied) = slashcommands::access::check_interaction(ctx, command).await {
+        let response = CommandResponse::text(denied.to_string()).ephemeral();
+        if let Err(why) = response.respond(&ctx.http, command).await {
+            warn!("Cannot respond to slash command: {}", why);
+        }
+        return "denied";
+    }
+    let credentials = ctx
+        .data
+        .read()
+        .await
+        .get::<CredentialsMap>()
+        .expect("Expected CredentialsMap in TypeMap.")
+        .clone();
+    if let Some(secret) = credentials.missing_for(&command.data.name) {
+        debug!(%secret, "Command needs a missing secret");
+        let response = CommandResponse::text(format!(
+            "`/{}` is not configured on this bot",
+            command.data.name
+        ))
+        .ephemeral();
+        if let Err(why) = response.respond(&ctx.http, command).await {
+            warn!("Cannot respond to slash command: {}", why);
+        }
+        return "disabled";
+    }
+    if let Err(cooldown) = cooldowns::check_interaction(ctx, command).await {
+        let response = CommandResponse::text(cooldown.to_string()).ephemeral();
+        if let Err(why) = response.respond(&ctx.http, command).await {
+            warn!("Cannot respond to slash command: {}", why);
+        }
+        return "cooldown";
+    }
+    let _ = command.defer(&ctx.http).await;
+    let market = ctx
+        .data
+        .read()
+        .await
+        .get::<MarketDataMap>()
+        .expect("Expected MarketDataMap in TypeMap.")
+        .clone();
+    // Err holds the reply for a command that failed, so it counts as an error in the stats
+    let result: Result<CommandResponse, CommandResponse> = match command.data.name.as_str() {
+        "floorprice" => {
+            slashcommands::floorprice::run(&market, command.guild_id, &command.data.options)
+                .await
+                .map(CommandResponse::text)
+                .map_err(CommandResponse::text)
+        }
+        "digest" => Ok(CommandResponse::text(
+            slashcommands::digest::run(ctx, command).await,
+        )),
+        "tag" => Ok(CommandResponse::text(
+            slashcommands::tag::run(ctx, command).await,
+        )),
+        "help" => Ok(slashcommands::help::run(ctx, command, &[&GENERAL_GROUP]).await),
+        "stats" => Ok(slashcommands::stats::run(ctx, command).await),
+        "settings" => Ok(CommandResponse::text(
+            slashcommands::settings::run(ctx, command).await,
+        )),
+        access::SETTINGS_COMMAND => Ok(CommandResponse::text(
+            slashcommands::access::run(ctx, command).await,
+        )),
+        "emoji" => Ok(slashcommands::emoji::run(ctx, command).await),
+        slashcommands::emoji::STEAL_CONTEXT_MENU => {
+            Ok(slashcommands::emoji::run_steal(ctx, command).await)
+        }
+        "coin" => {
+            match slashcommands::coin::run(&market, command.guild_id, &command.data.options).await {
+                Ok(cached) => {
+                    let chart = slashcommands::coin::chart(&ctx.data, &cached.value.pairs[0]).await;
+                    Ok(slashcommands::coin::response(&cached, chart))
+                }
+                Err(why) => Err(slashcommands::coin::error_response(&why)),
+            }
+        }
+        name => {
+            warn!(%name, "Unknown slash command");
+            Err(CommandResponse::text("Unknown command"))
+        }
+    };
+    let (response, outcome) = match result {
+        Ok(response) => (response, "ok"),
+        Err(response) => (response, "error"),
+    };
+
+    // The first followup after a defer replaces the "thinking" placeholder
+    if let Err(why) = command
+        .create_followup_message(&ctx.http, |followup| response.apply_followup(followup))
+        .await
+    {
+        warn!("Cannot respond to slash command: {}", why);
+        return "error";
+    }
+    outcome
+}
+
+fn member_span(event: &str, guild_id: GuildId, user: &User) -> Span {
+    debug_span!("event", event, guild = guild_id.0, user = user.id.0)
+}
+
+fn reaction_span(event: &str, reaction: &Reaction) -> Span {
+    debug_span!(
+        "event",
+        event,
+        guild = reaction.guild_id.map(|id| id.0),
+        user = reaction.user_id.map(|id| id.0)
+    )
+}
+
+async fn handle_reaction(ctx: Context, reaction: Reaction, add_role: bool) {
+    let data_read = ctx.data.read().await;
+    let message_data = data_read
+        .get::<MessageMap>()
+        .expect("Expected MessageMap in TypeMap.")
+        .clone();
+    if reaction.channel_id != ChannelId(message_data.load(Ordering::SeqCst)) {
+        return;
+    }
+    if let Some(user) = reaction.user_id {
+        if let Ok(struct_user) = user.to_user(&ctx.http).await {
+            if struct_user.bot {
+                return;
+            }
+        }
+    }
+
+    let reaction_roles_data = data_read
+        .get::<ReactionMap>()
+        .expect("Expected ReactionMap in TypeMap.")
+        .clone();
+
+    let reaction_roles = &*reaction_roles_data.read().await;
+    for (emoji, role_id) in reaction_roles {
+        if emoji != &reaction.emoji {
+            continue;
+        }
+
+        if let Some(guild_id) = reaction.guild_id {
+            if let Some(user_id) = reaction.user_id {
+                if let Ok(mut member) = guild_id.member(&ctx, user_id).await {
+                    let updated = roles::update(&ctx.http, &mut member, *role_id, add_role).await;
+                    let action = if add_role { "add" } else { "remove" };
+                    metrics::reaction_role(action, updated);
+                }
+            }
+        }
+    }
+}
+
+#[group]
+#[commands(
+    ping,
+    reactionroles,
+    floor,
+    steal,
+    assets,
+    latency,
+    shards,
+    restart_shard,
+    shutdown,
+    reload
+)]
+
+struct General;
+
+/// Whether `name` is a prefix command, one of its aliases or `help`. A tag with such
+/// a name would never run.
+pub fn is_reserved(name: &str) -> bool {
+    name == "help"
+        || GENERAL_GROUP
+            .options
+            .commands
+            .iter()
+            .any(|command| command.options.names.contains(&name))
+}
+
+#[hook]
+async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+    settings::message_prefix(ctx, msg).await
+}
+
+async fn command_timers(ctx: &Context) -> Arc<std::sync::Mutex<HashMap<MessageId, Instant>>> {
+    ctx.data
+        .read()
+        .await
+        .get::<CommandTimerMap>()
+        .expect("Expected CommandTimerMap in TypeMap.")
+        .clone()
+}
+
+#[hook]
+async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
+    let received = Instant::now();
+    let allowed = access::allow_message(ctx, msg, command_name).await
+        && cooldowns::allow_message(ctx, msg, command_name).await;
+    if allowed {
+        command_timers(ctx)
+            .await
+            .lock()
+            .expect("Command timers are never poisoned")
+            .insert(msg.id, received);
+    } else {
+        let span = logging::command_span("prefix", command_name, msg.guild_id, msg.author.id);
+        info!(parent: &span, outcome = "refused", "Command refused");
+        metrics::command("prefix", command_name, "refused");
+    }
+    allowed
+}
+
+#[hook]
+async fn unknown_command(ctx: &Context, msg: &Message, unknown_command_name: &str) {
+    let span = logging::command_span("custom", unknown_command_name, msg.guild_id, msg.author.id);
+    async {
+        let received = Instant::now();
+        let result = customcommands::dispatch(ctx, msg, unknown_command_name).await;
+        let invocation =
+            Invocation::from_message(msg, unknown_command_name, received, result.is_ok());
+        match result {
+            Ok(Dispatched::Unknown) => {
+                debug!("No command with this name");
+                return;
+            }
+            Ok(Dispatched::Refused) => {
+                info!(outcome = "refused", "Custom command refused");
+                metrics::command("custom", unknown_command_name, "refused");
+                return;
+            }
+            Ok(Dispatched::Answered) => info!(
+                latency_ms = invocation.duration_ms,
+                outcome = "ok",
+                "Custom command finished"
+            ),
+            Err(why) => warn!(
+                latency_ms = invocation.duration_ms,
+                outcome = "error",
+                "Custom command failed: {:?}",
+                why
+            ),
+        }
+        let outcome = if invocation.success { "ok" } else { "error" };
+        metrics::command("custom", unknown_command_name, outcome);
+        analytics::track(&ctx.data, invocation).await;
+    }
+    .instrument(span)
+    .await
+}
+
+#[hook]
+async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError, command_name: &str) {
+    let reply = match error {
+        DispatchError::LackingPermissions(permissions) => {
+            format!("You need the {} permission for that", permissions)
+        }
+        DispatchError::OnlyForGuilds => "That only works inside a server".to_string(),
+        DispatchError::OnlyForOwners => "That command is only for the bot owner".to_string(),
+        why => {
+            debug!(
+                command = command_name,
+                "Command was not dispatched: {:?}", why
+            );
+            return;
+        }
+    };
+    if let Err(why) = msg.channel_id.say(&ctx.http, reply).await {
+        warn!("Could not explain dispatch error: {}", why);
+    }
+}
+
+#[hook]
+async fn after(ctx: &Context, msg: &Message, command_name: &str, command_result: CommandResult) {
+    let span = logging::command_span("prefix", command_name, msg.guild_id, msg.author.id);
+    let received = command_timers(ctx)
+        .await
+        .lock()
+        .expect("Command timers are never poisoned")
+        .remove(&msg.id)
+        .unwrap_or_else(Instant::now);
+    let invocation = Invocation::from_message(msg, command_name, received, command_result.is_ok());
+    let outcome = if invocation.success { "ok" } else { "error" };
+    metrics::command("prefix", command_name, outcome);
+    match command_result {
+        Ok(()) => {
+            info!(parent: &span, latency_ms = invocation.duration_ms, outcome = "ok", "Command finished")
+        }
+        Err(why) => {
+            warn!(parent: &span, latency_ms = invocation.duration_ms, outcome = "error", "Command failed: {:?}", why)
+        }
+    }
+    analytics::track(&ctx.data, invocation).await;
+}
+
+/// Command line flags and the environment variable or secret each one stands in for.
+pub const FLAGS: &[(&str, &str)] = &[
+    ("--token", "DISCORD_TOKEN"),
+    ("--reservoir-key", "RESERVOIR_API_KEY"),
+    ("--opensea-key", "OPENSEA_API_KEY"),
+    ("--config", "CONFIG_PATH"),
+    ("--images", "IMAGES_PATH"),
+    ("--store", "STORE_PATH"),
+    ("--http-addr", "HTTP_ADDR"),
+    ("--log-level", "LOG_LEVEL"),
+    ("--log-format", "LOG_FORMAT"),
+];
+
+pub const USAGE: &str = "Usage: ronnbot [--flag value]...
+
+Every flag can also be set through the environment variable next to it,
+the flag wins when both are set.
+
+  --token          DISCORD_TOKEN      Bot token, required
+  --reservoir-key  RESERVOIR_API_KEY  Reservoir API key, for /floorprice
+  --opensea-key    OPENSEA_API_KEY    OpenSea API key, /floorprice falls back to it and
+                                      is off when neither key is set
+  --config         CONFIG_PATH        Config file, the built-in config when unset
+  --images         IMAGES_PATH        Images folder, defaults to images
+  --store          STORE_PATH         Where data is saved, defaults to store
+  --http-addr      HTTP_ADDR          Health and metrics address, defaults to 127.0.0.1:8080
+  --log-level      LOG_LEVEL          Tracing filter, defaults to info,serenity=warn
+  --log-format     LOG_FORMAT         text or json
+";
+
+/// Reads `--flag value` and `--flag=value` pairs, keyed by the matching environment variable.
+pub fn parse_flags(
+    args: impl IntoIterator<Item = String>,
+) -> anyhow::Result<HashMap<&'static str, String>> {
+    let mut values = HashMap::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let Some((_, key)) = FLAGS.iter().find(|(name, _)| *name == flag) else {
+            return Err(anyhow!("Unknown flag '{}'\n\n{}", flag, USAGE));
+        };
+        let value = match inline.or_else(|| args.next()) {
+            Some(value) => value,
+            None => return Err(anyhow!("'{}' needs a value", flag)),
+        };
+        values.insert(*key, value);
+    }
+    Ok(values)
+}
+
+/// Everything the client needs to start, wherever it was read from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotOptions {
+    pub token: String,
+    pub credentials: Credentials,
+    pub config_path: Option<PathBuf>,
+    pub images_path: PathBuf,
+    pub store_path: PathBuf,
+    pub http_addr: Option<String>,
+}
+
+impl BotOptions {
+    /// Reads each option by its environment variable name, Shuttle secrets use the same names.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let token =
+            lookup("DISCORD_TOKEN").ok_or_else(|| anyhow!("'DISCORD_TOKEN' was not found"))?;
+        Ok(Self {
+            token,
+            credentials: Credentials {
+                reservoir_api_key: lookup("RESERVOIR_API_KEY").filter(|key| !key.trim().is_empty()),
+                opensea_api_key: lookup("OPENSEA_API_KEY").filter(|key| !key.trim().is_empty()),
+            },
+            config_path: lookup("CONFIG_PATH").map(PathBuf::from),
+            images_path: lookup("IMAGES_PATH")
+                .unwrap_or_else(|| "images".to_string())
+                .into(),
+            store_path: lookup("STORE_PATH")
+                .unwrap_or_else(|| "store".to_string())
+                .into(),
+            http_addr: lookup("HTTP_ADDR"),
+        })
+    }
+}
+
+/// The NFT providers whose credentials are set.
+fn nft_providers(credentials: &Credentials) -> Vec<Arc<dyn NftFloorProvider>> {
+    let mut providers: Vec<Arc<dyn NftFloorProvider>> = vec![];
+    if let Some(key) = &credentials.reservoir_api_key {
+        providers.push(Arc::new(Reservoir::new(key.clone())));
+    }
+    if let Some(key) = &credentials.opensea_api_key {
+        providers.push(Arc::new(OpenSea::new(key.clone())));
+    }
+    providers
+}
+
+/// Whether the developer portal lets the bot receive member events. Unknown flags
+/// count as allowed, Discord then has the final say.
+pub fn members_intent_allowed(flags: Option<ApplicationFlags>) -> bool {
+    flags.is_none_or(|flags| {
+        flags.intersects(
+            ApplicationFlags::GATEWAY_GUILD_MEMBERS
+                | ApplicationFlags::GATEWAY_GUILD_MEMBERS_LIMITED,
+        )
+    })
+}
+
+/// Gateway intents, which decide what events the bot is notified about. The privileged
+/// `GUILD_MEMBERS` is only asked for when a guild has greetings and the portal allows
+/// it, Discord refuses the connection otherwise.
+pub fn intents(config: &Config, flags: Option<ApplicationFlags>) -> GatewayIntents {
+    let mut intents = GatewayIntents::GUILDS
+        | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::DIRECT_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
+        | GatewayIntents::GUILD_EMOJIS_AND_STICKERS;
+    if !config.greetings.is_empty() {
+        if members_intent_allowed(flags) {
+            intents |= GatewayIntents::GUILD_MEMBERS;
+        } else {
+            error!(
+                "Greetings are configured but the Server Members Intent is off, enable it on \
+                 the bot page of the developer portal. No welcome or goodbye messages are sent"
+            );
+        }
+    }
+    intents
+}
+
+/// Builds the client with every command, hook and `TypeMap` entry, and starts the
+/// health server. Stops the shards on ctrl+c.
+pub async fn build_client(options: BotOptions) -> anyhow::Result<Client> {
+    options.credentials.log_missing();
+    let store = Store::open(&options.store_path)?;
+    let http = Http::new(&options.token);
+    let config = Config::load(options.config_path.as_deref())?;
+
+    let info = http
+        .get_current_application_info()
+        .await
+        .context("Could not access application info")?;
+    let mut owners = HashSet::new();
+    owners.insert(info.owner.id);
+    let bot_id = http
+        .get_current_user()
+        .await
+        .context("Could not access the bot user")?
+        .id;
+    let framework = StandardFramework::new()
+        .configure(|c| {
+            c.owners(owners.clone())
+                .prefix("")
+                .dynamic_prefix(dynamic_prefix)
+                .on_mention(Some(bot_id))
+        })
+        .group(&GENERAL_GROUP)
+        .help(&HELP)
+        .before(before)
+        .unrecognised_command(unknown_command)
+        .on_dispatch_error(dispatch_error)
+        .after(after);
+    let intents = intents(&config, info.flags);
+
+    let client = Client::builder(&options.token, intents)
+        .event_handler(Bot)
+        .framework(framework)
+        .await
+        .context("Could not create the client")?;
+
+    {
+        let mut data = client.data.write().await;
+        data.insert::<ShardManagerMap>(client.shard_manager.clone());
+        data.insert::<OwnerMap>(Arc::new(owners));
+        data.insert::<MessageMap>(Arc::new(AtomicU64::new(config.channel_id)));
+        data.insert::<ReactionMap>(Arc::new(RwLock::new(config.reaction_roles()?)));
+        data.insert::<GreetingMap>(Arc::new(RwLock::new(config.greetings.clone())));
+        let pending_roles = store.load(greetings::STORE_KEY).await?.unwrap_or_default();
+        data.insert::<PendingRoleMap>(Arc::new(RwLock::new(pending_roles)));
+        data.insert::<ConfigPathMap>(options.config_path.clone());
+        let assets = Assets::index(&options.images_path);
+        let digests = store.load(scheduler::STORE_KEY).await?.unwrap_or_default();
+        data.insert::<DigestMap>(Arc::new(RwLock::new(digests)));
+        let access_rules = store.load(access::STORE_KEY).await?.unwrap_or_default();
+        data.insert::<AccessMap>(Arc::new(RwLock::new(access_rules)));
+        let settings = store.load(settings::STORE_KEY).await?.unwrap_or_default();
+        data.insert::<SettingsMap>(Arc::new(RwLock::new(settings)));
+        data.insert::<CooldownMap>(Arc::new(RwLock::new(Cooldowns::new(
+            config.cooldowns.clone(),
+        ))));
+        let command_stats = store.load(analytics::STORE_KEY).await?.unwrap_or_default();
+        data.insert::<AnalyticsMap>(Arc::new(RwLock::new(command_stats)));
+        let emoji_stats = store.load(emojistats::STORE_KEY).await?.unwrap_or_default();
+        data.insert::<EmojiStatsMap>(Arc::new(RwLock::new(emoji_stats)));
+        data.insert::<GuildEmojiMap>(Arc::default());
+        data.insert::<CommandTimerMap>(Arc::default());
+        let price_history = store
+            .load(slashcommands::coin::HISTORY_STORE_KEY)
+            .await?
+            .unwrap_or_default();
+        data.insert::<PriceHistoryMap>(Arc::new(RwLock::new(price_history)));
+        let custom_commands = customcommands::load(&store).await?;
+        customcommands::warn_missing_assets(&custom_commands, &assets);
+        data.insert::<BotMap>(Arc::new(assets));
+        data.insert::<CustomCommandMap>(Arc::new(RwLock::new(custom_commands)));
+        data.insert::<StoreMap>(Arc::new(store));
+        let api_client = Arc::new(ApiClient::default());
+        let market = MarketData::new(
+            api_client.clone(),
+            vec![
+                Arc::new(DexScreener::default()),
+                Arc::new(GeckoTerminal::default()),
+            ],
+            nft_providers(&options.credentials),
+            config.providers.clone(),
+        );
+        data.insert::<ApiClientMap>(api_client);
+        data.insert::<CredentialsMap>(Arc::new(options.credentials.clone()));
+        data.insert::<MarketDataMap>(Arc::new(market));
+    }
+    metrics::server::start(options.http_addr.as_deref(), client.shard_manager.clone());
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Could not register ctrl+c handler");
+        shard_manager.lock().await.shutdown_all().await;
+    });
+    Ok(client)
+}
@@ -0,0 +1,193 @@
+use serenity::client::bridge::gateway::{ShardId, ShardManager};
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::data::config::Config;
+use crate::data::{
+    configpathmap::ConfigPathMap, cooldownmap::CooldownMap, greetingmap::GreetingMap,
+    marketdatamap::MarketDataMap, messagemap::MessageMap, reactionmap::ReactionMap,
+    shardmanagermap::ShardManagerMap,
+};
+use crate::metrics::server::Health;
+use crate::ronn_utils::responder::CommandResponse;
+
+pub fn latency_response(health: &Health) -> CommandResponse {
+    if health.shards.is_empty() {
+        return CommandResponse::text("No shards are running");
+    }
+    let lines: Vec<String> = health
+        .shards
+        .iter()
+        .map(|shard| match shard.latency_ms {
+            Some(latency_ms) => format!("Shard {}: {} ms", shard.id, latency_ms),
+            None => format!("Shard {}: no heartbeat yet", shard.id),
+        })
+        .collect();
+    CommandResponse::text(lines.join("\n"))
+}
+
+pub fn shards_response(health: &Health) -> CommandResponse {
+    let connected = health
+        .shards
+        .iter()
+        .filter(|shard| shard.connected())
+        .count();
+    let mut lines = vec![format!(
+        "{} of {} shards connected",
+        connected,
+        health.shards.len()
+    )];
+    lines.extend(
+        health
+            .shards
+            .iter()
+            .map(|shard| format!("Shard {}: {}", shard.id, shard.stage)),
+    );
+    CommandResponse::text(lines.join("\n"))
+}
+
+/// Puts a freshly loaded config in place of the running one. Running cooldowns and
+/// cached market data are kept. Nothing changes when the reaction roles do not pair up.
+pub async fn apply_config(data: &RwLock<TypeMap>, config: Config) -> anyhow::Result<()> {
+    let reaction_roles = config.reaction_roles()?;
+    let data = data.read().await;
+    data.get::<MessageMap>()
+        .expect("Expected MessageMap in TypeMap.")
+        .store(config.channel_id, Ordering::SeqCst);
+    *data
+        .get::<ReactionMap>()
+        .expect("Expected ReactionMap in TypeMap.")
+        .write()
+        .await = reaction_roles;
+    *data
+        .get::<GreetingMap>()
+        .expect("Expected GreetingMap in TypeMap.")
+        .write()
+        .await = config.greetings;
+    data.get::<CooldownMap>()
+        .expect("Expected CooldownMap in TypeMap.")
+        .write()
+        .await
+        .set_config(config.cooldowns);
+    data.get::<MarketDataMap>()
+        .expect("Expected MarketDataMap in TypeMap.")
+        .set_config(config.providers);
+    Ok(())
+}
+
+async fn shard_manager(ctx: &Context) -> Arc<Mutex<ShardManager>> {
+    ctx.data
+        .read()
+        .await
+        .get::<ShardManagerMap>()
+        .expect("Expected ShardManagerMap in TypeMap.")
+        .clone()
+}
+
+#[command]
+#[description = "Show the gateway heartbeat latency of each shard"]
+#[owners_only]
+async fn latency(ctx: &Context, msg: &Message) -> CommandResult {
+    let shard_manager = shard_manager(ctx).await;
+    let health = Health::collect(&shard_manager).await;
+    latency_response(&health)
+        .send(&ctx.http, msg.channel_id)
+        .await?;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Show the connection stage of each shard"]
+#[owners_only]
+async fn shards(ctx: &Context, msg: &Message) -> CommandResult {
+    let shard_manager = shard_manager(ctx).await;
+    let health = Health::collect(&shard_manager).await;
+    shards_response(&health)
+        .send(&ctx.http, msg.channel_id)
+        .await?;
+
+    Ok(())
+}
+
+#[command("restart-shard")]
+#[description = "Reconnect one shard to the gateway"]
+#[usage = "<shard id>"]
+#[example = "0"]
+#[owners_only]
+async fn restart_shard(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let Ok(id) = args.single::<u64>() else {
+        msg.channel_id
+            .say(&ctx.http, "Which shard? Give its id")
+            .await?;
+        return Ok(());
+    };
+    let shard_manager = shard_manager(ctx).await;
+    if !shard_manager.lock().await.has(ShardId(id)).await {
+        msg.channel_id
+            .say(&ctx.http, format!("No shard {} is running", id))
+            .await?;
+        return Ok(());
+    }
+    // Say it first, the reply may go through the shard being restarted
+    msg.channel_id
+        .say(&ctx.http, format!("Restarting shard {}", id))
+        .await?;
+    info!(shard = id, user = msg.author.id.0, "Restarting shard");
+    shard_manager.lock().await.restart(ShardId(id)).await;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Disconnect every shard and stop the bot"]
+#[owners_only]
+async fn shutdown(ctx: &Context, msg: &Message) -> CommandResult {
+    msg.channel_id.say(&ctx.http, "Shutting down").await?;
+    info!(user = msg.author.id.0, "Shutting down");
+    shard_manager(ctx).await.lock().await.shutdown_all().await;
+
+    Ok(())
+}
+
+#[command]
+#[description = "Read the config again and apply reaction roles, greetings, cooldowns and providers"]
+#[owners_only]
+async fn reload(ctx: &Context, msg: &Message) -> CommandResult {
+    let path = ctx
+        .data
+        .read()
+        .await
+        .get::<ConfigPathMap>()
+        .expect("Expected ConfigPathMap in TypeMap.")
+        .clone();
+    let config = match Config::load(path.as_deref()) {
+        Ok(config) => config,
+        Err(why) => {
+            warn!("Config could not be reloaded: {:#}", why);
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("Config was not reloaded, keeping the old one: {:#}", why),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+    let reaction_roles = config.reaction_roles()?.len();
+    apply_config(&ctx.data, config).await?;
+    info!(user = msg.author.id.0, "Config reloaded");
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Config reloaded, {} reaction roles", reaction_roles),
+        )
+        .await?;
+
+    Ok(())
+}
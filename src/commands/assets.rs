@@ -0,0 +1,52 @@
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::CommandResult;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::customcommands::{CustomCommand, CustomCommands};
+use crate::data::{botmap::BotMap, customcommandmap::CustomCommandMap};
+use crate::ronn_utils::assets::Assets;
+use crate::ronn_utils::responder::CommandResponse;
+
+pub fn assets_response(assets: &Assets, commands: &CustomCommands) -> CommandResponse {
+    let mut lines: Vec<String> = assets
+        .files()
+        .map(|(name, size)| format!("`{}` ({} KB)", name, size.div_ceil(1024)))
+        .collect();
+    if lines.is_empty() {
+        lines.push("No images found".to_string());
+    }
+    let missing = assets.missing(commands.values().flat_map(CustomCommand::referenced_assets));
+    if !missing.is_empty() {
+        lines.push(format!(
+            "Missing: {}",
+            missing
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    CommandResponse::text(lines.join("\n"))
+}
+
+#[command]
+#[description = "List the image files and any that custom replies miss"]
+#[owners_only]
+async fn assets(ctx: &Context, msg: &Message) -> CommandResult {
+    let (assets, commands) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<BotMap>()
+                .expect("Expected BotMap in TypeMap.")
+                .clone(),
+            data.get::<CustomCommandMap>()
+                .expect("Expected CustomCommandMap in TypeMap.")
+                .clone(),
+        )
+    };
+    let response = assets_response(&assets, &*commands.read().await);
+    response.send(&ctx.http, msg.channel_id).await?;
+
+    Ok(())
+}
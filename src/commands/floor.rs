@@ -3,14 +3,18 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
+use crate::ronn_utils::responder::CommandResponse;
+
+pub fn floor_response() -> CommandResponse {
+    CommandResponse::text(
+        "This has been moved to a Slash command now try running /floorprice then collection ",
+    )
+}
+
 #[command]
+#[description = "Floor prices moved to /floorprice"]
 async fn floor(ctx: &Context, msg: &Message) -> CommandResult {
-    msg.channel_id
-        .say(
-            &ctx.http,
-            "This has been moved to a Slash command now try running /floorprice then collection ",
-        )
-        .await?;
+    floor_response().send(&ctx.http, msg.channel_id).await?;
 
     Ok(())
 }
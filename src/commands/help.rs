@@ -0,0 +1,410 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::framework::standard::macros::help;
+use serenity::framework::standard::{Args, CommandGroup, CommandResult, HelpOptions, OnlyIn};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::Colour;
+use std::collections::HashSet;
+
+use crate::access::{self, fetch_caller, Caller, GuildAccess};
+use crate::customcommands::CustomCommands;
+use crate::data::{
+    accessmap::AccessMap, customcommandmap::CustomCommandMap, ownermap::OwnerMap,
+    settingsmap::SettingsMap,
+};
+use crate::ronn_utils::responder::{CommandResponse, ResponseEmbed};
+use crate::settings;
+use crate::slashcommands;
+use tracing::warn;
+
+/// Commands shown on one page of the help embed.
+pub const PER_PAGE: usize = 5;
+/// Start of the custom id of the page buttons, `help:<user>:<page>`.
+pub const BUTTON_PREFIX: &str = "help";
+/// Discord's limit for an embed field value.
+const MAX_FIELD_LEN: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelpEntry {
+    /// How the command is invoked, `!steal` or `/emoji`.
+    pub name: String,
+    /// The bare name used to look the entry up.
+    pub command: String,
+    pub description: String,
+    pub usage: Option<String>,
+    pub examples: Vec<String>,
+}
+
+/// Who is asking for help, so only commands they can run are listed.
+#[derive(Debug, Clone, Default)]
+pub struct Viewer {
+    pub is_owner: bool,
+    pub guild_id: Option<u64>,
+    pub channel_id: u64,
+    pub caller: Option<Caller>,
+    pub access: Option<GuildAccess>,
+}
+
+impl Viewer {
+    /// Whether the access rules and Discord permissions let the viewer run `command`.
+    pub fn may_run(
+        &self,
+        command: &str,
+        permissions: Permissions,
+        owners_only: bool,
+        guild_only: bool,
+    ) -> bool {
+        if owners_only && !self.is_owner {
+            return false;
+        }
+        if self.guild_id.is_none() {
+            return !guild_only;
+        }
+        if access::check(
+            self.access.as_ref(),
+            command,
+            self.channel_id,
+            self.caller.as_ref(),
+        )
+        .is_err()
+        {
+            return false;
+        }
+        permissions.is_empty()
+            || self.caller.as_ref().is_some_and(|caller| {
+                caller.permissions.administrator() || caller.permissions.contains(permissions)
+            })
+    }
+
+    /// Looks up what is needed to filter help for `user_id`. `caller` is fetched when not given.
+    pub async fn fetch(
+        ctx: &Context,
+        guild_id: Option<GuildId>,
+        channel_id: ChannelId,
+        user_id: UserId,
+        caller: Option<Caller>,
+    ) -> Self {
+        let (owners, rules) = {
+            let data = ctx.data.read().await;
+            (
+                data.get::<OwnerMap>()
+                    .expect("Expected OwnerMap in TypeMap.")
+                    .clone(),
+                data.get::<AccessMap>()
+                    .expect("Expected AccessMap in TypeMap.")
+                    .clone(),
+            )
+        };
+        let mut viewer = Self {
+            is_owner: owners.contains(&user_id),
+            guild_id: guild_id.map(|id| id.0),
+            channel_id: channel_id.0,
+            caller,
+            access: None,
+        };
+        if let Some(guild_id) = guild_id {
+            viewer.access = rules.read().await.get(&guild_id.0).cloned();
+            if viewer.caller.is_none() {
+                match fetch_caller(ctx, guild_id, user_id).await {
+                    Ok(caller) => viewer.caller = Some(caller),
+                    Err(why) => warn!("Could not look up {} for help: {}", user_id, why),
+                }
+            }
+        }
+        viewer
+    }
+}
+
+/// The prefix commands of `groups` the viewer may run.
+pub fn prefix_entries(groups: &[&CommandGroup], prefix: &str, viewer: &Viewer) -> Vec<HelpEntry> {
+    groups
+        .iter()
+        .flat_map(|group| group.options.commands.iter())
+        .map(|command| command.options)
+        .filter(|options| options.help_available)
+        .filter(|options| {
+            viewer.may_run(
+                options.names[0],
+                options.required_permissions,
+                options.owners_only,
+                options.only_in == OnlyIn::Guild,
+            )
+        })
+        .map(|options| {
+            let name = format!("{}{}", prefix, options.names[0]);
+            HelpEntry {
+                command: options.names[0].to_string(),
+                description: options.desc.unwrap_or("No description").to_string(),
+                usage: options.usage.map(|usage| format!("{} {}", name, usage)),
+                examples: options
+                    .examples
+                    .iter()
+                    .map(|example| format!("{} {}", name, example))
+                    .collect(),
+                name,
+            }
+        })
+        .collect()
+}
+
+/// `/name sub|sub` for commands with subcommands, otherwise `/name <required> [optional]`.
+fn slash_usage(name: &str, options: &[serde_json::Value]) -> Option<String> {
+    if options.is_empty() {
+        return None;
+    }
+    let option_name =
+        |option: &serde_json::Value| option["name"].as_str().unwrap_or_default().to_string();
+    // Option type 1 is a subcommand
+    let subcommands: Vec<String> = options
+        .iter()
+        .filter(|option| option["type"].as_u64() == Some(1))
+        .map(option_name)
+        .collect();
+    if !subcommands.is_empty() {
+        return Some(format!("/{} {}", name, subcommands.join("|")));
+    }
+    let arguments: Vec<String> = options
+        .iter()
+        .map(|option| {
+            if option["required"].as_bool() == Some(true) {
+                format!("<{}>", option_name(option))
+            } else {
+                format!("[{}]", option_name(option))
+            }
+        })
+        .collect();
+    Some(format!("/{} {}", name, arguments.join(" ")))
+}
+
+/// The registered application commands the viewer may run.
+pub fn slash_entries(commands: &[CreateApplicationCommand], viewer: &Viewer) -> Vec<HelpEntry> {
+    commands
+        .iter()
+        .filter_map(|command| {
+            let name = command.0.get("name")?.as_str()?;
+            let permissions = command
+                .0
+                .get("default_member_permissions")
+                .and_then(|bits| bits.as_str()?.parse().ok())
+                .map(Permissions::from_bits_truncate)
+                .unwrap_or_else(Permissions::empty);
+            let guild_only =
+                command.0.get("dm_permission").and_then(|v| v.as_bool()) == Some(false);
+            if !viewer.may_run(name, permissions, false, guild_only) {
+                return None;
+            }
+            // Type 3 is a message context menu, which has no description
+            if command.0.get("type").and_then(|kind| kind.as_u64()) == Some(3) {
+                return Some(HelpEntry {
+                    name: format!("Apps > {}", name),
+                    command: name.to_lowercase(),
+                    description: "Right click a message and pick it under Apps".to_string(),
+                    usage: None,
+                    examples: vec![],
+                });
+            }
+            let options = command
+                .0
+                .get("options")
+                .and_then(|options| options.as_array().cloned())
+                .unwrap_or_default();
+            Some(HelpEntry {
+                name: format!("/{}", name),
+                command: name.to_string(),
+                description: command
+                    .0
+                    .get("description")
+                    .and_then(|description| description.as_str())
+                    .unwrap_or("No description")
+                    .to_string(),
+                usage: slash_usage(name, &options),
+                examples: vec![],
+            })
+        })
+        .collect()
+}
+
+/// One entry listing the custom replies and tags usable in the viewer's guild.
+pub fn custom_entry(commands: &CustomCommands, prefix: &str, viewer: &Viewer) -> Option<HelpEntry> {
+    let mut names: Vec<&str> = commands
+        .keys()
+        .filter_map(|key| match key.split_once(':') {
+            Some((guild, name))
+                if Some(guild) == viewer.guild_id.map(|id| id.to_string()).as_deref() =>
+            {
+                Some(name)
+            }
+            Some(_) => None,
+            None => Some(key.as_str()),
+        })
+        .filter(|name| viewer.may_run(name, Permissions::empty(), false, false))
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    names.sort_unstable();
+    names.dedup();
+    let mut description = String::new();
+    for (index, name) in names.iter().enumerate() {
+        let entry = format!("`{}{}` ", prefix, name);
+        let more = format!("and {} more", names.len() - index);
+        if description.len() + entry.len() + more.len() > MAX_FIELD_LEN {
+            description.push_str(&more);
+            break;
+        }
+        description.push_str(&entry);
+    }
+    Some(HelpEntry {
+        name: "Custom replies".to_string(),
+        command: "tags".to_string(),
+        description: description.trim_end().to_string(),
+        usage: Some("/tag create to add your own".to_string()),
+        examples: vec![],
+    })
+}
+
+fn field_value(entry: &HelpEntry) -> String {
+    let mut value = entry.description.clone();
+    if let Some(usage) = &entry.usage {
+        value.push_str(&format!("\nUsage: `{}`", usage));
+    }
+    for example in &entry.examples {
+        value.push_str(&format!("\nExample: `{}`", example));
+    }
+    value
+}
+
+pub fn page_count(entries: &[HelpEntry]) -> usize {
+    entries.len().div_ceil(PER_PAGE).max(1)
+}
+
+/// One page of help with buttons for the others, which only `user_id` can use.
+pub fn page(entries: &[HelpEntry], page: usize, user_id: u64) -> CommandResponse {
+    let pages = page_count(entries);
+    let page = page.min(pages - 1);
+    let embed = ResponseEmbed {
+        title: Some("Commands you can use".to_string()),
+        fields: entries
+            .iter()
+            .skip(page * PER_PAGE)
+            .take(PER_PAGE)
+            .map(|entry| (entry.name.clone(), field_value(entry), false))
+            .collect(),
+        colour: Some(Colour::BLURPLE),
+        footer: Some(format!(
+            "Page {} of {}, add a command name to help for details",
+            page + 1,
+            pages
+        )),
+        ..Default::default()
+    };
+    let response = CommandResponse::embed(embed);
+    if pages == 1 {
+        return response;
+    }
+    response
+        .with_button(
+            format!("{}:{}:{}", BUTTON_PREFIX, user_id, page.saturating_sub(1)),
+            "Previous",
+            page == 0,
+        )
+        .with_button(
+            format!("{}:{}:{}", BUTTON_PREFIX, user_id, page + 1),
+            "Next",
+            page + 1 == pages,
+        )
+}
+
+/// The user and page of a help button.
+pub fn parse_button(custom_id: &str) -> Option<(u64, usize)> {
+    let mut parts = custom_id.split(':');
+    if parts.next()? != BUTTON_PREFIX {
+        return None;
+    }
+    let user_id = parts.next()?.parse().ok()?;
+    let page = parts.next()?.parse().ok()?;
+    Some((user_id, page))
+}
+
+/// Help for one command, by name with or without its prefix or slash.
+pub fn details(entries: &[HelpEntry], query: &str) -> Option<CommandResponse> {
+    let query = query.trim().to_lowercase();
+    let entry = entries.iter().find(|entry| {
+        entry.name.to_lowercase() == query
+            || entry.command == query.trim_start_matches(|c: char| !c.is_alphanumeric())
+    })?;
+    let mut fields = vec![];
+    if let Some(usage) = &entry.usage {
+        fields.push(("Usage".to_string(), format!("`{}`", usage), false));
+    }
+    if !entry.examples.is_empty() {
+        let examples: Vec<String> = entry
+            .examples
+            .iter()
+            .map(|example| format!("`{}`", example))
+            .collect();
+        fields.push(("Examples".to_string(), examples.join("\n"), false));
+    }
+    Some(CommandResponse::embed(ResponseEmbed {
+        title: Some(entry.name.clone()),
+        description: Some(entry.description.clone()),
+        fields,
+        colour: Some(Colour::BLURPLE),
+        ..Default::default()
+    }))
+}
+
+/// Everything the viewer can run: prefix commands, slash commands, then custom replies.
+pub async fn entries(ctx: &Context, groups: &[&CommandGroup], viewer: &Viewer) -> Vec<HelpEntry> {
+    let (settings, commands) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<SettingsMap>()
+                .expect("Expected SettingsMap in TypeMap.")
+                .clone(),
+            data.get::<CustomCommandMap>()
+                .expect("Expected CustomCommandMap in TypeMap.")
+                .clone(),
+        )
+    };
+    let prefix = {
+        let settings = settings.read().await;
+        let guild = viewer.guild_id.and_then(|id| settings.get(&id));
+        settings::prefixes(guild).remove(0)
+    };
+    let mut entries = prefix_entries(groups, &prefix, viewer);
+    entries.extend(slash_entries(&slashcommands::registry(), viewer));
+    entries.extend(custom_entry(&*commands.read().await, &prefix, viewer));
+    entries
+}
+
+/// Answers `query` with details or, without one, the first page.
+pub fn response(entries: &[HelpEntry], query: &str, user_id: u64) -> CommandResponse {
+    if query.trim().is_empty() {
+        return page(entries, 0, user_id);
+    }
+    details(entries, query).unwrap_or_else(|| {
+        CommandResponse::text(format!(
+            "There is no command called `{}` that you can use",
+            query.trim()
+        ))
+    })
+}
+
+#[help]
+async fn help(
+    ctx: &Context,
+    msg: &Message,
+    args: Args,
+    _options: &'static HelpOptions,
+    groups: &[&'static CommandGroup],
+    _owners: HashSet<UserId>,
+) -> CommandResult {
+    let viewer = Viewer::fetch(ctx, msg.guild_id, msg.channel_id, msg.author.id, None).await;
+    let entries = entries(ctx, groups, &viewer).await;
+    response(&entries, args.message(), msg.author.id.0)
+        .send(&ctx.http, msg.channel_id)
+        .await?;
+
+    Ok(())
+}
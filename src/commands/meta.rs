@@ -3,9 +3,16 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
+use crate::ronn_utils::responder::CommandResponse;
+
+pub fn ping_response() -> CommandResponse {
+    CommandResponse::text("Pong!")
+}
+
 #[command]
+#[description = "Check that the bot is awake"]
 async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
-    msg.channel_id.say(&ctx.http, "Pong!").await?;
+    ping_response().send(&ctx.http, msg.channel_id).await?;
 
     Ok(())
 }
@@ -1,4 +1,7 @@
+pub mod admin;
+pub mod assets;
 pub mod floor;
+pub mod help;
 pub mod meta;
 pub mod reactionroles;
-pub mod replycommands;
+pub mod steal;
@@ -0,0 +1,271 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::http::Http;
+use serenity::model::guild::PremiumTier;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::parse_emoji;
+
+use crate::data::apiclientmap::ApiClientMap;
+use crate::ronn_utils::apiclient::ApiClient;
+use crate::ronn_utils::responder::CommandResponse;
+use crate::ronn_utils::utils::custom_emojis;
+
+/// Discord rejects emoji images above this size.
+pub const MAX_EMOJI_BYTES: u64 = 256 * 1024;
+
+/// Something to turn into an emoji, with the name it came with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StealSource {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StealRequest {
+    pub sources: Vec<StealSource>,
+    /// From `name=`, used for every source with a number appended after the first.
+    pub rename: Option<String>,
+    /// What could not be used and why.
+    pub failures: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageKind {
+    pub mime: &'static str,
+    pub animated: bool,
+}
+
+impl StealRequest {
+    /// Reads custom emojis, image URLs and `name=` from the command arguments.
+    pub fn parse(args: &str) -> Self {
+        let mut request = Self::default();
+        for arg in args.split_whitespace() {
+            if let Some(name) = arg.strip_prefix("name=") {
+                request.rename = Some(name.to_string());
+            } else if let Some(emoji) = parse_emoji(arg) {
+                request.sources.push(StealSource {
+                    url: emoji.url(),
+                    name: emoji.name,
+                });
+            } else if arg.starts_with("https://") {
+                request.sources.push(StealSource {
+                    name: url_name(arg),
+                    url: arg.to_string(),
+                });
+            } else if arg.starts_with("http://") {
+                request.failures.push((
+                    arg.to_string(),
+                    "only https image URLs can be used".to_string(),
+                ));
+            } else {
+                request.failures.push((
+                    arg.to_string(),
+                    "not a custom emoji or image URL".to_string(),
+                ));
+            }
+        }
+        request
+    }
+
+    /// Every custom emoji anywhere in `text`, once each.
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            sources: custom_emojis(text)
+                .into_iter()
+                .map(|emoji| StealSource {
+                    url: emoji.url(),
+                    name: emoji.name,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds the images attached to the message.
+    pub fn add_attachments(&mut self, attachments: &[Attachment]) {
+        for attachment in attachments {
+            let is_image = attachment
+                .content_type
+                .as_deref()
+                .is_some_and(|kind| kind.starts_with("image/"));
+            if is_image {
+                self.sources.push(StealSource {
+                    name: url_name(&attachment.filename),
+                    url: attachment.url.clone(),
+                });
+            } else {
+                self.failures.push((
+                    attachment.filename.clone(),
+                    "attachment is not an image".to_string(),
+                ));
+            }
+        }
+    }
+
+    pub fn add_stickers(&mut self, stickers: &[StickerItem]) {
+        for sticker in stickers {
+            match sticker.image_url() {
+                Some(url) => self.sources.push(StealSource {
+                    name: sticker.name.clone(),
+                    url,
+                }),
+                None => self.failures.push((
+                    sticker.name.clone(),
+                    "this sticker format cannot be an emoji".to_string(),
+                )),
+            }
+        }
+    }
+
+    /// Final emoji name for every source, in order.
+    pub fn names(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .enumerate()
+            .map(|(index, source)| match (&self.rename, index) {
+                (Some(rename), 0) => emoji_name(rename),
+                (Some(rename), _) => emoji_name(&format!("{}_{}", rename, index + 1)),
+                (None, _) => emoji_name(&source.name),
+            })
+            .collect()
+    }
+}
+
+/// The file stem of a URL or filename, without query string.
+fn url_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let file = path.rsplit('/').next().unwrap_or_default();
+    file.split('.').next().unwrap_or_default().to_string()
+}
+
+/// Emoji names are 2 to 32 letters, digits or underscores.
+pub fn emoji_name(raw: &str) -> String {
+    let mut name: String = raw
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .take(32)
+        .collect();
+    if name.len() < 2 {
+        name = format!("emoji{}", name);
+    }
+    name
+}
+
+/// Tells the image format from its first bytes, only formats Discord takes for emojis.
+pub fn image_kind(data: &[u8]) -> Option<ImageKind> {
+    let kind = |mime, animated| Some(ImageKind { mime, animated });
+    match data {
+        [0x89, b'P', b'N', b'G', ..] => kind("image/png", false),
+        [0xFF, 0xD8, 0xFF, ..] => kind("image/jpeg", false),
+        [b'G', b'I', b'F', b'8', ..] => kind("image/gif", true),
+        _ => None,
+    }
+}
+
+/// Static and animated emojis each have this many slots.
+pub fn slot_limit(tier: PremiumTier) -> usize {
+    match tier {
+        PremiumTier::Tier1 => 100,
+        PremiumTier::Tier2 => 150,
+        PremiumTier::Tier3 => 250,
+        _ => 50,
+    }
+}
+
+pub fn summary(added: &[String], failures: &[(String, String)]) -> CommandResponse {
+    let mut lines = vec![];
+    if !added.is_empty() {
+        lines.push(format!("Added {}", added.join(" ")));
+    }
+    for (what, why) in failures {
+        lines.push(format!("Could not add `{}`: {}", what, why));
+    }
+    if lines.is_empty() {
+        lines.push("Please supply some emojis, stickers, images or image URLs".to_string());
+    }
+    CommandResponse::text(lines.join("\n"))
+}
+
+/// Downloads every source and adds it to the guild, returning one summary.
+pub async fn add_emojis(
+    http: &Http,
+    client: &ApiClient,
+    guild_id: GuildId,
+    mut request: StealRequest,
+) -> serenity::Result<CommandResponse> {
+    let mut failures = std::mem::take(&mut request.failures);
+    let mut added = vec![];
+    if request.sources.is_empty() {
+        return Ok(summary(&added, &failures));
+    }
+
+    let guild = guild_id.to_partial_guild(http).await?;
+    let limit = slot_limit(guild.premium_tier);
+    let mut animated = guild.emojis.values().filter(|e| e.animated).count();
+    let mut still = guild.emojis.len() - animated;
+
+    for (source, name) in request.sources.iter().zip(request.names()) {
+        let data = match client.download(&source.url, MAX_EMOJI_BYTES).await {
+            Ok(data) => data,
+            Err(why) => {
+                failures.push((name, why.to_string()));
+                continue;
+            }
+        };
+        let Some(kind) = image_kind(&data) else {
+            failures.push((name, "not a PNG, JPEG or GIF image".to_string()));
+            continue;
+        };
+        let used = if kind.animated {
+            &mut animated
+        } else {
+            &mut still
+        };
+        if *used >= limit {
+            let slot = if kind.animated { "animated" } else { "static" };
+            failures.push((name, format!("all {} {} emoji slots are used", limit, slot)));
+            continue;
+        }
+        let image = format!("data:{};base64,{}", kind.mime, STANDARD.encode(&data));
+        match guild_id.create_emoji(http, &name, &image).await {
+            Ok(emoji) => {
+                *used += 1;
+                added.push(emoji.to_string());
+            }
+            Err(why) => failures.push((name, why.to_string())),
+        }
+    }
+    Ok(summary(&added, &failures))
+}
+
+#[command]
+#[description = "Add emojis, stickers or images from elsewhere to this server"]
+#[usage = "<emojis, image URLs or attachments> [name=new_name]"]
+#[example = "<:pepe:123456789012345678> name=frog"]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_EMOJIS_AND_STICKERS")]
+async fn steal(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let Some(guild_id) = msg.guild_id else {
+        return Ok(());
+    };
+    let mut request = StealRequest::parse(args.message());
+    request.add_attachments(&msg.attachments);
+    request.add_stickers(&msg.sticker_items);
+    let client = ctx
+        .data
+        .read()
+        .await
+        .get::<ApiClientMap>()
+        .expect("Expected ApiClientMap in TypeMap.")
+        .clone();
+
+    add_emojis(&ctx.http, &client, guild_id, request)
+        .await?
+        .send(&ctx.http, msg.channel_id)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use crate::data::config::{Bucket, CooldownConfig, CooldownRule};
+use crate::data::cooldownmap::CooldownMap;
+use serenity::model::channel::Message;
+use serenity::model::prelude::interaction::application_command::ApplicationCommandInteraction;
+use serenity::prelude::Context;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How long the notice about a cooldown stays before it is deleted.
+pub const NOTICE_SECONDS: u64 = 5;
+
+/// Where a command was used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scope {
+    pub user_id: u64,
+    pub channel_id: u64,
+    pub guild_id: Option<u64>,
+}
+
+impl Scope {
+    fn id(&self, bucket: Bucket) -> u64 {
+        match bucket {
+            Bucket::User => self.user_id,
+            Bucket::Channel => self.channel_id,
+            // A DM is its own guild
+            Bucket::Guild => self.guild_id.unwrap_or(self.channel_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnCooldown {
+    pub command: String,
+    pub bucket: Bucket,
+    pub remaining: Duration,
+    /// False once the caller was told about this cooldown, so spamming gets one notice.
+    pub notify: bool,
+}
+
+impl fmt::Display for OnCooldown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whom = match self.bucket {
+            Bucket::User => "for you",
+            Bucket::Channel => "in this channel",
+            Bucket::Guild => "in this server",
+        };
+        // Round up so a cooldown never reads as 0 seconds
+        let seconds = self.remaining.as_secs() + u64::from(self.remaining.subsec_nanos() > 0);
+        write!(
+            f,
+            "`{}` is on cooldown {}, try again in {}s",
+            self.command, whom, seconds
+        )
+    }
+}
+
+struct Entry {
+    until: Instant,
+    notified: bool,
+}
+
+/// Running cooldowns, kept in memory only.
+pub struct Cooldowns {
+    config: CooldownConfig,
+    entries: HashMap<(String, Bucket, u64), Entry>,
+}
+
+impl Cooldowns {
+    pub fn new(config: CooldownConfig) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Swaps the rules, running cooldowns keep their end time.
+    pub fn set_config(&mut self, config: CooldownConfig) {
+        self.config = config;
+    }
+
+    pub fn rules(&self, command: &str) -> &[CooldownRule] {
+        self.config
+            .commands
+            .get(command)
+            .unwrap_or(&self.config.default)
+    }
+
+    /// Starts the cooldowns of `command`, or says which one is still running.
+    pub fn check(&mut self, command: &str, scope: Scope, now: Instant) -> Result<(), OnCooldown> {
+        let command = command.to_lowercase();
+        self.entries.retain(|_, entry| entry.until > now);
+        let rules = self.rules(&command).to_vec();
+
+        let blocked = rules
+            .iter()
+            .filter_map(|rule| {
+                let key = (command.clone(), rule.bucket, scope.id(rule.bucket));
+                let entry = self.entries.get(&key)?;
+                Some((key, entry.until - now))
+            })
+            .max_by_key(|(_, remaining)| *remaining);
+        if let Some((key, remaining)) = blocked {
+            let entry = self.entries.get_mut(&key).expect("blocked entries exist");
+            let notify = !entry.notified;
+            entry.notified = true;
+            return Err(OnCooldown {
+                command,
+                bucket: key.1,
+                remaining,
+                notify,
+            });
+        }
+
+        for rule in rules.iter().filter(|rule| rule.seconds > 0) {
+            self.entries.insert(
+                (command.clone(), rule.bucket, scope.id(rule.bucket)),
+                Entry {
+                    until: now + Duration::from_secs(rule.seconds),
+                    notified: false,
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+async fn check(ctx: &Context, command: &str, scope: Scope) -> Result<(), OnCooldown> {
+    let cooldowns = ctx
+        .data
+        .read()
+        .await
+        .get::<CooldownMap>()
+        .expect("Expected CooldownMap in TypeMap.")
+        .clone();
+    let result = cooldowns
+        .write()
+        .await
+        .check(command, scope, Instant::now());
+    result
+}
+
+/// Checks a prefix command, posting a short lived notice the first time it is refused.
+/// Returns whether the command may run.
+pub async fn allow_message(ctx: &Context, msg: &Message, command: &str) -> bool {
+    let scope = Scope {
+        user_id: msg.author.id.0,
+        channel_id: msg.channel_id.0,
+        guild_id: msg.guild_id.map(|id| id.0),
+    };
+    let Err(cooldown) = check(ctx, command, scope).await else {
+        return true;
+    };
+    if cooldown.notify {
+        match msg.reply(&ctx.http, cooldown.to_string()).await {
+            Ok(notice) => {
+                let http = ctx.http.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_secs(NOTICE_SECONDS)).await;
+                    let _ = notice.delete(&http).await;
+                });
+            }
+            Err(why) => warn!("Could not send cooldown notice: {}", why),
+        }
+    }
+    false
+}
+
+/// Checks a slash command, the caller is told through an ephemeral reply.
+pub async fn check_interaction(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), OnCooldown> {
+    let scope = Scope {
+        user_id: command.user.id.0,
+        channel_id: command.channel_id.0,
+        guild_id: command.guild_id.map(|id| id.0),
+    };
+    check(ctx, &command.data.name, scope).await
+}
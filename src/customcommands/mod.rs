@@ -0,0 +1,300 @@
+use crate::data::botmap::BotMap;
+use crate::data::customcommandmap::CustomCommandMap;
+use crate::data::storemap::StoreMap;
+use crate::ronn_utils::assets::Assets;
+use crate::ronn_utils::responder::{CommandResponse, ResponseEmbed};
+use crate::ronn_utils::store::{persist, Dirty, Store};
+use crate::{access, cooldowns};
+use chrono::{DateTime, Utc};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serenity::model::channel::Message;
+use serenity::prelude::{Context, RwLock, TypeMap};
+use serenity::utils::Colour;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const STORE_KEY: &str = "custom_commands";
+
+/// Use counts changed since the last save.
+static USES: Dirty = Dirty::new();
+
+/// Replies shipped with the bot, used to seed the store on first start.
+const DEFAULTS: &str = include_str!("../../custom_commands.json");
+
+pub mod template;
+
+use template::TemplateContext;
+use tracing::warn;
+
+/// A prefix command defined as data: text, files from the images folder and an embed.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomCommand {
+    /// Reply text, may use the placeholders from [`template::render`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// When set, one of these is used instead of `content`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content_pool: Vec<String>,
+    /// Filenames in the static images folder.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    /// One of these images folder files is attached on top of `attachments`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachment_pool: Vec<String>,
+    /// Files uploaded through /tag, kept in the store.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uploads: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embed: Option<CustomEmbed>,
+    /// Who created it with /tag, `None` for replies shipped with the bot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub uses: u64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomEmbed {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Either a URL or a filename in the images folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colour: Option<u32>,
+}
+
+/// Custom commands by [`key`]. Built-in replies work everywhere, tags only in their guild.
+pub type CustomCommands = HashMap<String, CustomCommand>;
+
+pub fn key(guild_id: Option<u64>, name: &str) -> String {
+    match guild_id {
+        Some(guild_id) => format!("{}:{}", guild_id, name.to_lowercase()),
+        None => name.to_lowercase(),
+    }
+}
+
+/// The guild's own tag wins over a built-in reply of the same name.
+pub fn find<'a>(
+    commands: &'a CustomCommands,
+    guild_id: Option<u64>,
+    name: &str,
+) -> Option<(String, &'a CustomCommand)> {
+    guild_id
+        .map(|guild_id| key(Some(guild_id), name))
+        .into_iter()
+        .chain(std::iter::once(key(None, name)))
+        .find_map(|key| commands.get(&key).map(|command| (key, command)))
+}
+
+pub fn defaults() -> CustomCommands {
+    serde_json::from_str(DEFAULTS).expect("custom_commands.json is not valid")
+}
+
+/// Loads the registry from the store, seeding it with the defaults only when it was never
+/// saved. A corrupt entry is an error, it is not replaced.
+pub async fn load(store: &Store) -> anyhow::Result<CustomCommands> {
+    let stored: Option<CustomCommands> = store.load(STORE_KEY).await?;
+    Ok(match stored {
+        Some(commands) => commands,
+        None => {
+            let commands = defaults();
+            if let Err(why) = store.save(STORE_KEY, &commands).await {
+                warn!("Could not seed custom commands: {}", why);
+            }
+            commands
+        }
+    })
+}
+
+impl CustomCommand {
+    /// Fills in the templates and picks from the pools. Returns the response without
+    /// files and the images folder files to attach.
+    pub fn render(
+        &self,
+        context: &TemplateContext,
+        rng: &mut impl Rng,
+    ) -> (CommandResponse, Vec<String>) {
+        let content = self
+            .content_pool
+            .choose(rng)
+            .or(self.content.as_ref())
+            .map(|content| template::render(content, context, rng));
+        let mut response = CommandResponse {
+            content,
+            ..Default::default()
+        };
+        let mut files = self.attachments.clone();
+        files.extend(self.attachment_pool.choose(rng).cloned());
+        if let Some(embed) = &self.embed {
+            let image = embed.image.as_ref().map(|image| {
+                if image.contains("://") {
+                    image.clone()
+                } else {
+                    if !files.contains(image) {
+                        files.push(image.clone());
+                    }
+                    format!("attachment://{}", image)
+                }
+            });
+            response.embed = Some(ResponseEmbed {
+                title: embed
+                    .title
+                    .as_ref()
+                    .map(|title| template::render(title, context, rng)),
+                url: embed.url.clone(),
+                description: embed
+                    .description
+                    .as_ref()
+                    .map(|description| template::render(description, context, rng)),
+                colour: embed.colour.map(Colour::new),
+                image,
+                ..Default::default()
+            });
+        }
+        (response, files)
+    }
+
+    /// Images folder files this command may attach.
+    pub fn referenced_assets(&self) -> impl Iterator<Item = &str> {
+        let embed_image = self
+            .embed
+            .as_ref()
+            .and_then(|embed| embed.image.as_deref())
+            .filter(|image| !image.contains("://"));
+        self.attachments
+            .iter()
+            .chain(&self.attachment_pool)
+            .map(String::as_str)
+            .chain(embed_image)
+    }
+
+    /// Attachments are read from disk, so a missing image is an error rather than a panic.
+    pub async fn response(
+        &self,
+        assets: &Assets,
+        store: &Store,
+        context: &TemplateContext,
+    ) -> std::io::Result<CommandResponse> {
+        let (mut response, files) = self.render(context, &mut rand::thread_rng());
+        for filename in files {
+            let data = assets.read(&filename).await?;
+            response = response.with_attachment(filename, data);
+        }
+        for filename in &self.uploads {
+            let data = store.load_file(filename).await?;
+            response = response.with_attachment(filename.clone(), data);
+        }
+        Ok(response)
+    }
+}
+
+/// Warns about images referenced by custom commands that are not in the folder.
+pub fn warn_missing_assets(commands: &CustomCommands, assets: &Assets) {
+    let missing = assets.missing(commands.values().flat_map(CustomCommand::referenced_assets));
+    for name in missing {
+        warn!(
+            "Custom commands reference image {} which is not in the images folder",
+            name
+        );
+    }
+}
+
+/// What `dispatch` did with a message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dispatched {
+    /// No custom command has that name.
+    Unknown,
+    /// The access rules or a cooldown stopped it, the member was told.
+    Refused,
+    Answered,
+}
+
+/// Spawns the loop that saves use counts every minute. Only the first call starts it.
+pub fn start(data: Arc<RwLock<TypeMap>>) {
+    USES.start("Custom command usage", Duration::from_secs(60), move || {
+        let data = data.clone();
+        async move { persist::<CustomCommandMap, _>(&data, STORE_KEY).await }
+    });
+}
+
+/// Answers `msg` if `name` is a custom command and counts the use.
+pub async fn dispatch(ctx: &Context, msg: &Message, name: &str) -> anyhow::Result<Dispatched> {
+    let exists = {
+        let data = ctx.data.read().await;
+        let commands = data
+            .get::<CustomCommandMap>()
+            .expect("Expected CustomCommandMap in TypeMap.")
+            .read()
+            .await;
+        find(&commands, msg.guild_id.map(|id| id.0), name).is_some()
+    };
+    if !exists {
+        return Ok(Dispatched::Unknown);
+    }
+    if !access::allow_message(ctx, msg, name).await
+        || !cooldowns::allow_message(ctx, msg, name).await
+    {
+        return Ok(Dispatched::Refused);
+    }
+    let (command, assets, store) = {
+        let data = ctx.data.read().await;
+        let mut commands = data
+            .get::<CustomCommandMap>()
+            .expect("Expected CustomCommandMap in TypeMap.")
+            .write()
+            .await;
+        let guild_id = msg.guild_id.map(|id| id.0);
+        let Some((key, _)) = find(&commands, guild_id, name) else {
+            return Ok(Dispatched::Unknown);
+        };
+        let command = commands.get_mut(&key).expect("Key was just found");
+        command.uses += 1;
+        let assets = data
+            .get::<BotMap>()
+            .expect("Expected BotMap in TypeMap.")
+            .clone();
+        let store = data
+            .get::<StoreMap>()
+            .expect("Expected StoreMap in TypeMap.")
+            .clone();
+        (command.clone(), assets, store)
+    };
+    USES.mark();
+    let context = TemplateContext {
+        author_id: msg.author.id.0,
+        channel_id: msg.channel_id.0,
+        args: msg
+            .content
+            .split_whitespace()
+            // Skips the prefix, which can be a mention followed by a space
+            .skip_while(|word| !word.to_lowercase().ends_with(&name.to_lowercase()))
+            .skip(1)
+            .map(str::to_string)
+            .collect(),
+        count: command.uses,
+        user_name: Some(msg.author.name.clone()),
+        ..Default::default()
+    };
+    let response = match command.response(&assets, &store, &context).await {
+        Ok(response) => response,
+        Err(why) => {
+            warn!("Custom command '{}' could not load a file: {}", name, why);
+            CommandResponse::text(format!(
+                "Sorry, this command is broken right now ({}), let an admin know",
+                why
+            ))
+        }
+    };
+    response.send(&ctx.http, msg.channel_id).await?;
+    Ok(Dispatched::Answered)
+}
@@ -0,0 +1,86 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serenity::utils::parse_username;
+
+/// Everything a reply template can refer to.
+#[derive(Default, Debug, Clone)]
+pub struct TemplateContext {
+    pub author_id: u64,
+    pub channel_id: u64,
+    /// Words after the command name.
+    pub args: Vec<String>,
+    /// How often the command has been used, including this time.
+    pub count: u64,
+    /// The author's username, for `{name}`.
+    pub user_name: Option<String>,
+    /// For `{server}` and `{members}`, only known to greetings.
+    pub guild_name: Option<String>,
+    pub member_count: Option<u64>,
+}
+
+/// Fills in `{user}`, `{name}`, `{mention:arg}`, `{channel}`, `{server}`, `{members}`,
+/// `{random:a|b|c}`, `{args}` and `{count}`. Anything else between braces, or a placeholder
+/// the context has no value for, is left untouched.
+pub fn render(template: &str, context: &TemplateContext, rng: &mut impl Rng) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        match expand(&rest[1..end], context, rng) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn expand(placeholder: &str, context: &TemplateContext, rng: &mut impl Rng) -> Option<String> {
+    let (name, arg) = match placeholder.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (placeholder, None),
+    };
+    match (name.trim(), arg) {
+        ("user", None) => Some(format!("<@{}>", context.author_id)),
+        ("channel", None) => Some(format!("<#{}>", context.channel_id)),
+        ("args", None) => Some(context.args.join(" ")),
+        ("count", None) => Some(context.count.to_string()),
+        ("name", None) => context.user_name.clone(),
+        ("server", None) => context.guild_name.clone(),
+        ("members", None) => context.member_count.map(|count| count.to_string()),
+        ("mention", Some(arg)) => Some(mention(arg.trim(), context)),
+        ("random", Some(choices)) => choices
+            .split('|')
+            .collect::<Vec<_>>()
+            .choose(rng)
+            .map(|choice| choice.to_string()),
+        _ => None,
+    }
+}
+
+/// `{mention:2}` mentions whoever the second argument names, falling back to the author,
+/// while `{mention:<user id>}` always mentions that user.
+fn mention(arg: &str, context: &TemplateContext) -> String {
+    match arg.parse::<u64>() {
+        Ok(position) if position < 100 => {
+            let target = position
+                .checked_sub(1)
+                .and_then(|index| context.args.get(index as usize));
+            match target {
+                Some(target) => match parse_username(target) {
+                    Some(id) => format!("<@{}>", id),
+                    None if target.parse::<u64>().is_ok() => format!("<@{}>", target),
+                    None => target.clone(),
+                },
+                None => format!("<@{}>", context.author_id),
+            }
+        }
+        Ok(id) => format!("<@{}>", id),
+        Err(_) => format!("<@{}>", context.author_id),
+    }
+}
@@ -0,0 +1,10 @@
+use crate::access::AccessRules;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct AccessMap;
+
+impl TypeMapKey for AccessMap {
+    type Value = Arc<RwLock<AccessRules>>;
+}
@@ -0,0 +1,10 @@
+use crate::analytics::CommandStats;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct AnalyticsMap;
+
+impl TypeMapKey for AnalyticsMap {
+    type Value = Arc<RwLock<CommandStats>>;
+}
@@ -0,0 +1,9 @@
+use crate::ronn_utils::apiclient::ApiClient;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+
+pub struct ApiClientMap;
+
+impl TypeMapKey for ApiClientMap {
+    type Value = Arc<ApiClient>;
+}
@@ -1,8 +1,9 @@
+use crate::ronn_utils::assets::Assets;
 use serenity::prelude::TypeMapKey;
-use std::path::PathBuf;
+use std::sync::Arc;
 
-pub(crate) struct BotMap;
+pub struct BotMap;
 
 impl TypeMapKey for BotMap {
-    type Value = PathBuf;
+    type Value = Arc<Assets>;
 }
@@ -0,0 +1,12 @@
+use serenity::model::id::MessageId;
+use serenity::prelude::TypeMapKey;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// When each running prefix command was received, by the message that ran it.
+pub struct CommandTimerMap;
+
+impl TypeMapKey for CommandTimerMap {
+    type Value = Arc<Mutex<HashMap<MessageId, Instant>>>;
+}
@@ -1,8 +1,213 @@
+use crate::customcommands::CustomCommand;
+use anyhow::{bail, Context};
 use serde::{Deserialize, Serialize};
+use serenity::model::channel::ReactionType;
+use serenity::model::id::RoleId;
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::warn;
+
+/// Used when no config file is set.
+pub const DEFAULT_CONFIG: &str = r#"{
+    "channel_id": 1027793756872769536,
+    "emotes": [
+        "<a:gib:956543324410507284>",
+        "<a:fraggy_spit:1084701921392218172>",
+        "🦜",
+        "<:pepefingerping:956560593819693087>",
+        "🪙"
+    ],
+    "role_ids": [
+        1027794564133691453, 968428801728069662, 1041328459877724250,
+        1055667323534573709, 1088778873883336755
+    ],
+    "providers": {
+        "tokens": ["dexscreener", "geckoterminal"],
+        "nfts": ["reservoir", "opensea"],
+        "guilds": {}
+    },
+    "cooldowns": {
+        "default": [{ "bucket": "user", "seconds": 3 }],
+        "commands": {
+            "ilv": [
+                { "bucket": "user", "seconds": 60 },
+                { "bucket": "channel", "seconds": 30 }
+            ],
+            "digest": [{ "bucket": "guild", "seconds": 30 }]
+        }
+    }
+ }"#;
 
 #[derive(Serialize, Deserialize)]
-pub(crate) struct Config {
-    pub(crate) channel_id: u64,
-    pub(crate) emotes: Vec<String>,
-    pub(crate) role_ids: Vec<u64>,
+pub struct Config {
+    pub channel_id: u64,
+    pub emotes: Vec<String>,
+    pub role_ids: Vec<u64>,
+    #[serde(default)]
+    pub providers: ProviderConfig,
+    #[serde(default)]
+    pub cooldowns: CooldownConfig,
+    /// Welcome and goodbye messages by guild id.
+    #[serde(default)]
+    pub greetings: HashMap<u64, GreetingConfig>,
+}
+
+/// What happens when members join or leave one guild.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct GreetingConfig {
+    /// Where welcome and goodbye messages go, none are sent when unset.
+    #[serde(default)]
+    pub channel_id: Option<u64>,
+    /// Sent when a member joins, uses the custom command format and placeholders.
+    #[serde(default)]
+    pub welcome: Option<CustomCommand>,
+    #[serde(default)]
+    pub goodbye: Option<CustomCommand>,
+    /// Given to every member who joins.
+    #[serde(default)]
+    pub auto_roles: Vec<u64>,
+    /// Wait this long before giving `auto_roles`, e.g. until membership screening is done.
+    #[serde(default)]
+    pub role_delay_seconds: u64,
+}
+
+/// Secrets for optional integrations. A missing one turns off the commands that need it
+/// instead of stopping the bot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Credentials {
+    pub reservoir_api_key: Option<String>,
+    pub opensea_api_key: Option<String>,
+}
+
+/// Commands by the secrets they cannot work without, any one of the secrets is enough.
+const REQUIRED_BY: &[(&[&str], &[&str])] =
+    &[(&["RESERVOIR_API_KEY", "OPENSEA_API_KEY"], &["floorprice"])];
+
+impl Credentials {
+    fn get(&self, secret: &str) -> Option<&str> {
+        match secret {
+            "RESERVOIR_API_KEY" => self.reservoir_api_key.as_deref(),
+            "OPENSEA_API_KEY" => self.opensea_api_key.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn has_any(&self, secrets: &[&str]) -> bool {
+        secrets.iter().any(|secret| self.get(secret).is_some())
+    }
+
+    /// The secrets `command` is missing, if any, as `A or B`.
+    pub fn missing_for(&self, command: &str) -> Option<String> {
+        REQUIRED_BY
+            .iter()
+            .find(|(secrets, commands)| commands.contains(&command) && !self.has_any(secrets))
+            .map(|(secrets, _)| secrets.join(" or "))
+    }
+
+    /// Logs each missing secret and the commands it turns off.
+    pub fn log_missing(&self) {
+        for (secrets, commands) in REQUIRED_BY {
+            if !self.has_any(secrets) {
+                let secret = secrets.join(" or ");
+                warn!(
+                    %secret,
+                    "{} is not set, disabling {}",
+                    secret,
+                    commands
+                        .iter()
+                        .map(|command| format!("/{}", command))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file at `path`, or the built-in config when there is none.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let config: Self = match path {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .with_context(|| format!("Config {} could not be read", path.display()))?;
+                serde_json::from_str(&text)
+                    .with_context(|| format!("Config {} could not be parsed", path.display()))?
+            }
+            None => serde_json::from_str(DEFAULT_CONFIG)?,
+        };
+        config.reaction_roles()?;
+        Ok(config)
+    }
+
+    /// Emotes paired with the role they give, skipping any emote that does not parse.
+    /// Errors when `emotes` and `role_ids` differ in length, as roles would be dropped.
+    pub fn reaction_roles(&self) -> anyhow::Result<Vec<(ReactionType, RoleId)>> {
+        if self.emotes.len() != self.role_ids.len() {
+            bail!(
+                "Config has {} emotes but {} role_ids, they are paired in order",
+                self.emotes.len(),
+                self.role_ids.len()
+            );
+        }
+        Ok(self
+            .emotes
+            .iter()
+            .zip(&self.role_ids)
+            .filter_map(
+                |(emote, role_id)| match ReactionType::try_from(emote.as_str()) {
+                    Ok(emoji) => Some((emoji, RoleId(*role_id))),
+                    Err(_) => {
+                        warn!("Reaction role emote '{}' is not valid, skipping it", emote);
+                        None
+                    }
+                },
+            )
+            .collect())
+    }
+}
+
+/// Preferred market data providers, by name, tried in order before the rest.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    #[serde(default)]
+    pub nfts: Vec<String>,
+    /// Per guild overrides keyed by guild id.
+    #[serde(default)]
+    pub guilds: HashMap<u64, GuildProviderConfig>,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct GuildProviderConfig {
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    #[serde(default)]
+    pub nfts: Vec<String>,
+}
+
+/// Who shares a cooldown: one member, everyone in a channel, or the whole guild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    User,
+    Channel,
+    Guild,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CooldownRule {
+    pub bucket: Bucket,
+    pub seconds: u64,
+}
+
+/// Cooldowns by command name, prefix, custom and slash alike.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct CooldownConfig {
+    /// Used for every command without its own entry.
+    #[serde(default)]
+    pub default: Vec<CooldownRule>,
+    #[serde(default)]
+    pub commands: HashMap<String, Vec<CooldownRule>>,
 }
@@ -0,0 +1,9 @@
+use serenity::prelude::TypeMapKey;
+use std::path::PathBuf;
+
+/// Where `!reload` reads the config from, `None` for the built-in one.
+pub struct ConfigPathMap;
+
+impl TypeMapKey for ConfigPathMap {
+    type Value = Option<PathBuf>;
+}
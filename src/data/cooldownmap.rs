@@ -0,0 +1,10 @@
+use crate::cooldowns::Cooldowns;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct CooldownMap;
+
+impl TypeMapKey for CooldownMap {
+    type Value = Arc<RwLock<Cooldowns>>;
+}
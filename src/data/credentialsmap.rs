@@ -0,0 +1,9 @@
+use crate::data::config::Credentials;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+
+pub struct CredentialsMap;
+
+impl TypeMapKey for CredentialsMap {
+    type Value = Arc<Credentials>;
+}
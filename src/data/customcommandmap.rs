@@ -0,0 +1,10 @@
+use crate::customcommands::CustomCommands;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct CustomCommandMap;
+
+impl TypeMapKey for CustomCommandMap {
+    type Value = Arc<RwLock<CustomCommands>>;
+}
@@ -0,0 +1,10 @@
+use crate::scheduler::Schedules;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct DigestMap;
+
+impl TypeMapKey for DigestMap {
+    type Value = Arc<RwLock<Schedules>>;
+}
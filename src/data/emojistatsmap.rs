@@ -0,0 +1,10 @@
+use crate::emojistats::EmojiStats;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct EmojiStatsMap;
+
+impl TypeMapKey for EmojiStatsMap {
+    type Value = Arc<RwLock<EmojiStats>>;
+}
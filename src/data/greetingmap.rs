@@ -0,0 +1,10 @@
+use crate::data::config::GreetingConfig;
+use serenity::prelude::{RwLock, TypeMapKey};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct GreetingMap;
+
+impl TypeMapKey for GreetingMap {
+    type Value = Arc<RwLock<HashMap<u64, GreetingConfig>>>;
+}
@@ -0,0 +1,10 @@
+use crate::emojistats::GuildEmojis;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct GuildEmojiMap;
+
+impl TypeMapKey for GuildEmojiMap {
+    type Value = Arc<RwLock<GuildEmojis>>;
+}
@@ -0,0 +1,9 @@
+use crate::providers::MarketData;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+
+pub struct MarketDataMap;
+
+impl TypeMapKey for MarketDataMap {
+    type Value = Arc<MarketData>;
+}
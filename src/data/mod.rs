@@ -1,4 +1,23 @@
+pub mod accessmap;
+pub mod analyticsmap;
+pub mod apiclientmap;
 pub mod botmap;
+pub mod commandtimermap;
 pub mod config;
+pub mod configpathmap;
+pub mod cooldownmap;
+pub mod credentialsmap;
+pub mod customcommandmap;
+pub mod digestmap;
+pub mod emojistatsmap;
+pub mod greetingmap;
+pub mod guildemojimap;
+pub mod marketdatamap;
 pub mod messagemap;
+pub mod ownermap;
+pub mod pendingrolemap;
+pub mod pricehistorymap;
 pub mod reactionmap;
+pub mod settingsmap;
+pub mod shardmanagermap;
+pub mod storemap;
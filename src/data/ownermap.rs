@@ -0,0 +1,11 @@
+use serenity::model::id::UserId;
+use serenity::prelude::TypeMapKey;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The bot owners, for checks outside the framework.
+pub struct OwnerMap;
+
+impl TypeMapKey for OwnerMap {
+    type Value = Arc<HashSet<UserId>>;
+}
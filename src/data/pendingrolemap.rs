@@ -0,0 +1,10 @@
+use crate::greetings::PendingRoles;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct PendingRoleMap;
+
+impl TypeMapKey for PendingRoleMap {
+    type Value = Arc<RwLock<PendingRoles>>;
+}
@@ -0,0 +1,10 @@
+use crate::slashcommands::coin::PriceHistory;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct PriceHistoryMap;
+
+impl TypeMapKey for PriceHistoryMap {
+    type Value = Arc<RwLock<PriceHistory>>;
+}
@@ -2,7 +2,7 @@ use serenity::{model::channel::ReactionType, model::id::RoleId, prelude::TypeMap
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-pub(crate) struct ReactionMap;
+pub struct ReactionMap;
 
 impl TypeMapKey for ReactionMap {
     type Value = Arc<RwLock<Vec<(ReactionType, RoleId)>>>;
@@ -0,0 +1,10 @@
+use crate::settings::Settings;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct SettingsMap;
+
+impl TypeMapKey for SettingsMap {
+    type Value = Arc<RwLock<Settings>>;
+}
@@ -0,0 +1,9 @@
+use serenity::client::bridge::gateway::ShardManager;
+use serenity::prelude::{Mutex, TypeMapKey};
+use std::sync::Arc;
+
+pub struct ShardManagerMap;
+
+impl TypeMapKey for ShardManagerMap {
+    type Value = Arc<Mutex<ShardManager>>;
+}
@@ -0,0 +1,9 @@
+use crate::ronn_utils::store::Store;
+use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
+
+pub struct StoreMap;
+
+impl TypeMapKey for StoreMap {
+    type Value = Arc<Store>;
+}
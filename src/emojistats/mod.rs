@@ -0,0 +1,206 @@
+use crate::data::{emojistatsmap::EmojiStatsMap, guildemojimap::GuildEmojiMap};
+use crate::ronn_utils::store::{persist, Dirty};
+use chrono::{DateTime, Utc};
+use serenity::model::guild::Emoji;
+use serenity::prelude::{RwLock, TypeMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const STORE_KEY: &str = "emoji_stats";
+/// Daily counts older than this are dropped.
+pub const RETENTION_DAYS: i64 = 90;
+/// How many emojis the most and least used lists show.
+const TOP: usize = 10;
+
+/// Counts changed since the last save.
+static CHANGES: Dirty = Dirty::new();
+
+/// Uses per day (days since the epoch) for one emoji.
+pub type DailyCounts = BTreeMap<i64, u64>;
+/// Emoji id to its daily counts.
+pub type GuildEmojiStats = HashMap<u64, DailyCounts>;
+/// Guild id to the stats of the emojis used there.
+pub type EmojiStats = HashMap<u64, GuildEmojiStats>;
+/// Guild id to the ids of its own emojis, from the gateway.
+pub type GuildEmojis = HashMap<u64, HashSet<u64>>;
+
+pub fn day(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(24 * 60 * 60)
+}
+
+/// Counts one use of each emoji that belongs to the guild, emojis from other servers
+/// are skipped so they cannot grow the stats.
+pub fn record(
+    stats: &mut EmojiStats,
+    guild_id: u64,
+    guild_emojis: &HashSet<u64>,
+    emoji_ids: impl IntoIterator<Item = u64>,
+    now: DateTime<Utc>,
+) {
+    let today = day(now);
+    for emoji_id in emoji_ids {
+        if !guild_emojis.contains(&emoji_id) {
+            continue;
+        }
+        let days = stats
+            .entry(guild_id)
+            .or_default()
+            .entry(emoji_id)
+            .or_default();
+        *days.entry(today).or_default() += 1;
+    }
+}
+
+/// Drops days past the retention window, then emojis and guilds with no days left.
+pub fn prune(stats: &mut EmojiStats, now: DateTime<Utc>) {
+    let oldest = day(now) - RETENTION_DAYS;
+    for guild in stats.values_mut() {
+        for days in guild.values_mut() {
+            days.retain(|day, _| *day > oldest);
+        }
+        guild.retain(|_, days| !days.is_empty());
+    }
+    stats.retain(|_, guild| !guild.is_empty());
+}
+
+/// Uses of an emoji over the last `days` days, or all retained days.
+pub fn total(
+    stats: Option<&GuildEmojiStats>,
+    emoji_id: u64,
+    days: Option<i64>,
+    now: DateTime<Utc>,
+) -> u64 {
+    let since = days.map_or(i64::MIN, |days| day(now) - days + 1);
+    stats
+        .and_then(|stats| stats.get(&emoji_id))
+        .map(|counts| counts.range(since..).map(|(_, count)| count).sum())
+        .unwrap_or_default()
+}
+
+/// Most and least used of the guild's emojis, plus the ones nobody used in the window.
+pub fn report(
+    emojis: &[Emoji],
+    stats: Option<&GuildEmojiStats>,
+    days: Option<i64>,
+    now: DateTime<Utc>,
+) -> String {
+    if emojis.is_empty() {
+        return "This server has no emojis".to_string();
+    }
+    let mut counted: Vec<(&Emoji, u64)> = emojis
+        .iter()
+        .map(|emoji| (emoji, total(stats, emoji.id.0, days, now)))
+        .collect();
+    counted.sort_by(|(a, a_count), (b, b_count)| {
+        b_count
+            .cmp(a_count)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    let (used, unused): (Vec<_>, Vec<_>) = counted.into_iter().partition(|(_, count)| *count > 0);
+    let line = |entries: &[(&Emoji, u64)]| {
+        entries
+            .iter()
+            .map(|(emoji, count)| format!("{} {}", emoji, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut lines = vec![format!(
+        "Emoji use in the last {} days",
+        days.unwrap_or(RETENTION_DAYS)
+    )];
+    if used.is_empty() {
+        lines.push("No emoji use recorded yet".to_string());
+    } else {
+        lines.push(format!("Most used: {}", line(&used[..used.len().min(TOP)])));
+        if used.len() > TOP {
+            let least: Vec<_> = used.iter().rev().take(TOP).copied().collect();
+            lines.push(format!("Least used: {}", line(&least)));
+        }
+    }
+    if !unused.is_empty() {
+        let never: Vec<String> = unused.iter().map(|(emoji, _)| emoji.to_string()).collect();
+        lines.push(format!("Not used ({}): {}", never.len(), never.join(" ")));
+    }
+    let mut reply = lines.join("\n");
+    if reply.len() > 2000 {
+        let cut = reply
+            .char_indices()
+            .map(|(index, _)| index)
+            .take_while(|index| *index <= 1997)
+            .last()
+            .unwrap_or_default();
+        reply.truncate(cut);
+        reply.push_str("...");
+    }
+    reply
+}
+
+/// Records emoji use from an event handler, saving happens in the background.
+pub async fn track(data: &Arc<RwLock<TypeMap>>, guild_id: u64, emoji_ids: Vec<u64>) {
+    if emoji_ids.is_empty() {
+        return;
+    }
+    let (stats, guild_emojis) = {
+        let data_read = data.read().await;
+        (
+            data_read
+                .get::<EmojiStatsMap>()
+                .expect("Expected EmojiStatsMap in TypeMap.")
+                .clone(),
+            data_read
+                .get::<GuildEmojiMap>()
+                .expect("Expected GuildEmojiMap in TypeMap.")
+                .clone(),
+        )
+    };
+    let guild_emojis = guild_emojis.read().await;
+    let Some(known) = guild_emojis.get(&guild_id) else {
+        // The guild was not sent by the gateway yet
+        return;
+    };
+    record(
+        &mut *stats.write().await,
+        guild_id,
+        known,
+        emoji_ids,
+        Utc::now(),
+    );
+    CHANGES.mark();
+}
+
+/// Replaces the emojis known for a guild, on guild create and emoji updates.
+pub async fn set_guild_emojis(
+    data: &Arc<RwLock<TypeMap>>,
+    guild_id: u64,
+    emoji_ids: impl IntoIterator<Item = u64>,
+) {
+    let guild_emojis = data
+        .read()
+        .await
+        .get::<GuildEmojiMap>()
+        .expect("Expected GuildEmojiMap in TypeMap.")
+        .clone();
+    guild_emojis
+        .write()
+        .await
+        .insert(guild_id, emoji_ids.into_iter().collect());
+}
+
+/// Spawns the loop that prunes and saves changed stats every minute. Only the first call
+/// starts it.
+pub fn start(data: Arc<RwLock<TypeMap>>) {
+    CHANGES.start("Emoji stats", Duration::from_secs(60), move || {
+        let data = data.clone();
+        async move {
+            let stats = data
+                .read()
+                .await
+                .get::<EmojiStatsMap>()
+                .expect("Expected EmojiStatsMap in TypeMap.")
+                .clone();
+            prune(&mut *stats.write().await, Utc::now());
+            persist::<EmojiStatsMap, _>(&data, STORE_KEY).await
+        }
+    });
+}
@@ -0,0 +1,276 @@
+use crate::customcommands::template::TemplateContext;
+use crate::customcommands::CustomCommand;
+use crate::data::config::GreetingConfig;
+use crate::data::{
+    botmap::BotMap, greetingmap::GreetingMap, pendingrolemap::PendingRoleMap, storemap::StoreMap,
+};
+use crate::roles;
+use crate::ronn_utils::store::persist;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::http::{Http, HttpError};
+use serenity::model::guild::Member;
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use serenity::model::user::User;
+use serenity::prelude::{Context, RwLock, SerenityError, TypeMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+pub const STORE_KEY: &str = "pending_roles";
+/// How long a grant waits before it is tried again after Discord failed it.
+pub const RETRY_SECONDS: i64 = 60;
+/// Discord's JSON error code for "Unknown Member".
+const UNKNOWN_MEMBER: isize = 10007;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Placeholder values for a member joining or leaving, `{user}` is the member.
+pub fn context(
+    user_id: u64,
+    user_name: &str,
+    channel_id: u64,
+    guild_name: Option<String>,
+    member_count: Option<u64>,
+) -> TemplateContext {
+    TemplateContext {
+        author_id: user_id,
+        channel_id,
+        user_name: Some(user_name.to_string()),
+        guild_name,
+        member_count,
+        ..Default::default()
+    }
+}
+
+async fn config(ctx: &Context, guild_id: GuildId) -> Option<GreetingConfig> {
+    let greetings = ctx
+        .data
+        .read()
+        .await
+        .get::<GreetingMap>()
+        .expect("Expected GreetingMap in TypeMap.")
+        .clone();
+    let greetings = greetings.read().await;
+    greetings.get(&guild_id.0).cloned()
+}
+
+/// The guild name and member count, without the cache this takes a request.
+async fn guild_details(ctx: &Context, guild_id: GuildId) -> (Option<String>, Option<u64>) {
+    match guild_id.to_partial_guild_with_counts(&ctx.http).await {
+        Ok(guild) => (Some(guild.name), guild.approximate_member_count),
+        Err(why) => {
+            debug!("Guild details could not be fetched: {}", why);
+            (None, None)
+        }
+    }
+}
+
+async fn send(ctx: &Context, message: &CustomCommand, channel_id: u64, context: &TemplateContext) {
+    let (assets, store) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<BotMap>()
+                .expect("Expected BotMap in TypeMap.")
+                .clone(),
+            data.get::<StoreMap>()
+                .expect("Expected StoreMap in TypeMap.")
+                .clone(),
+        )
+    };
+    let response = match message.response(&assets, &store, context).await {
+        Ok(response) => response,
+        Err(why) => {
+            warn!("Greeting could not load a file: {}", why);
+            return;
+        }
+    };
+    if let Err(why) = response.send(&ctx.http, ChannelId(channel_id)).await {
+        warn!(channel = channel_id, "Greeting could not be sent: {}", why);
+    }
+}
+
+/// Roles a member gets once `due` has passed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingGrant {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub role_ids: Vec<u64>,
+    pub due: DateTime<Utc>,
+}
+
+/// Grants waiting out `role_delay_seconds`, saved so a restart does not lose them.
+pub type PendingRoles = Vec<PendingGrant>;
+
+/// Adds a grant, replacing the one of a member who left and joined again.
+pub fn queue(pending: &mut PendingRoles, grant: PendingGrant) {
+    pending.retain(|old| (old.guild_id, old.user_id) != (grant.guild_id, grant.user_id));
+    pending.push(grant);
+}
+
+/// Removes and returns the grants that are due.
+pub fn take_due(pending: &mut PendingRoles, now: DateTime<Utc>) -> Vec<PendingGrant> {
+    let (due, waiting) = std::mem::take(pending)
+        .into_iter()
+        .partition(|grant| grant.due <= now);
+    *pending = waiting;
+    due
+}
+
+/// Puts a failed grant back to be tried again later, unless the member joined again
+/// and already has a newer one.
+pub fn retry(pending: &mut PendingRoles, mut grant: PendingGrant, now: DateTime<Utc>) {
+    if pending
+        .iter()
+        .any(|old| (old.guild_id, old.user_id) == (grant.guild_id, grant.user_id))
+    {
+        return;
+    }
+    grant.due = now + ChronoDuration::seconds(RETRY_SECONDS);
+    pending.push(grant);
+}
+
+/// Whether Discord answered that the member is not in the guild anymore, the only
+/// failure after which a grant is dropped.
+pub fn member_gone(why: &SerenityError) -> bool {
+    match why {
+        SerenityError::Http(http) => match &**http {
+            HttpError::UnsuccessfulRequest(response) => {
+                response.status_code.as_u16() == 404 && response.error.code == UNKNOWN_MEMBER
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+async fn save_pending(data: &RwLock<TypeMap>) {
+    if let Err(why) = persist::<PendingRoleMap, _>(data, STORE_KEY).await {
+        error!("Pending roles could not be saved: {:#}", why);
+    }
+}
+
+/// Gives the configured roles now, or queues them when there is a delay.
+async fn give_auto_roles(ctx: &Context, member: &Member, config: &GreetingConfig) {
+    if config.auto_roles.is_empty() {
+        return;
+    }
+    if config.role_delay_seconds == 0 {
+        let role_ids: Vec<RoleId> = config.auto_roles.iter().copied().map(RoleId).collect();
+        let _ = roles::add_all(&ctx.http, &mut member.clone(), &role_ids).await;
+        return;
+    }
+    let pending = ctx
+        .data
+        .read()
+        .await
+        .get::<PendingRoleMap>()
+        .expect("Expected PendingRoleMap in TypeMap.")
+        .clone();
+    let grant = PendingGrant {
+        guild_id: member.guild_id.0,
+        user_id: member.user.id.0,
+        role_ids: config.auto_roles.clone(),
+        due: Utc::now() + ChronoDuration::seconds(config.role_delay_seconds as i64),
+    };
+    queue(&mut *pending.write().await, grant);
+    save_pending(&ctx.data).await;
+}
+
+/// Spawns the loop that gives delayed roles once they are due. Safe to call on every
+/// `ready`, only the first call starts it.
+pub fn start(http: Arc<Http>, data: Arc<RwLock<TypeMap>>) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(10));
+        loop {
+            interval.tick().await;
+            give_due_roles(&http, &data).await;
+        }
+    });
+}
+
+async fn give_due_roles(http: &Arc<Http>, data: &RwLock<TypeMap>) {
+    let pending = data
+        .read()
+        .await
+        .get::<PendingRoleMap>()
+        .expect("Expected PendingRoleMap in TypeMap.")
+        .clone();
+    let due = take_due(&mut *pending.write().await, Utc::now());
+    if due.is_empty() {
+        return;
+    }
+    let mut failed = Vec::new();
+    for grant in due {
+        // Fetched now, they may have left in the meantime
+        let result = match GuildId(grant.guild_id).member(http, grant.user_id).await {
+            Ok(mut member) => {
+                let role_ids: Vec<RoleId> = grant.role_ids.iter().copied().map(RoleId).collect();
+                roles::add_all(http, &mut member, &role_ids).await
+            }
+            Err(why) => Err(why),
+        };
+        match result {
+            Ok(()) => {}
+            Err(why) if member_gone(&why) => {
+                debug!(
+                    user = grant.user_id,
+                    "Member left before their roles were given"
+                );
+            }
+            Err(why) => {
+                warn!(
+                    guild = grant.guild_id,
+                    user = grant.user_id,
+                    "Delayed roles could not be given, retrying in {}s: {}",
+                    RETRY_SECONDS,
+                    why
+                );
+                failed.push(grant);
+            }
+        }
+    }
+    if !failed.is_empty() {
+        let mut pending = pending.write().await;
+        for grant in failed {
+            retry(&mut pending, grant, Utc::now());
+        }
+    }
+    save_pending(data).await;
+}
+
+/// Sends the welcome message and gives the auto roles of the member's guild.
+pub async fn welcome(ctx: &Context, member: &Member) {
+    let Some(config) = config(ctx, member.guild_id).await else {
+        return;
+    };
+    if let (Some(channel_id), Some(welcome)) = (config.channel_id, &config.welcome) {
+        let (guild_name, member_count) = guild_details(ctx, member.guild_id).await;
+        let context = context(
+            member.user.id.0,
+            &member.user.name,
+            channel_id,
+            guild_name,
+            member_count,
+        );
+        send(ctx, welcome, channel_id, &context).await;
+    }
+    give_auto_roles(ctx, member, &config).await;
+}
+
+/// Sends the goodbye message of the guild the user left.
+pub async fn farewell(ctx: &Context, guild_id: GuildId, user: &User) {
+    let Some(config) = config(ctx, guild_id).await else {
+        return;
+    };
+    let (Some(channel_id), Some(goodbye)) = (config.channel_id, &config.goodbye) else {
+        return;
+    };
+    let (guild_name, member_count) = guild_details(ctx, guild_id).await;
+    let context = context(user.id.0, &user.name, channel_id, guild_name, member_count);
+    send(ctx, goodbye, channel_id, &context).await;
+}
@@ -0,0 +1,16 @@
+pub mod access;
+pub mod analytics;
+pub mod bot;
+pub mod commands;
+pub mod cooldowns;
+pub mod customcommands;
+pub mod data;
+pub mod emojistats;
+pub mod greetings;
+pub mod metrics;
+pub mod providers;
+pub mod roles;
+pub mod ronn_utils;
+pub mod scheduler;
+pub mod settings;
+pub mod slashcommands;
@@ -0,0 +1,177 @@
+pub mod server;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Every metric the bot exports, as (name, type, help). Rendered in this order.
+const FAMILIES: &[(&str, &str, &str)] = &[
+    (
+        "ronnbot_commands_total",
+        "counter",
+        "Commands handled, by kind, command and outcome.",
+    ),
+    (
+        "ronnbot_api_request_seconds",
+        "summary",
+        "Time spent on each request to an upstream API, by host.",
+    ),
+    (
+        "ronnbot_api_errors_total",
+        "counter",
+        "Failed upstream API requests, by host and error.",
+    ),
+    (
+        "ronnbot_reaction_roles_total",
+        "counter",
+        "Reaction roles given or taken, by action and outcome.",
+    ),
+    (
+        "ronnbot_cache_requests_total",
+        "counter",
+        "Market data cache lookups, by cache and result.",
+    ),
+];
+
+type Labels = Vec<(&'static str, String)>;
+
+/// In-process counters rendered in the Prometheus text format by `/metrics`.
+pub struct Registry {
+    /// Keyed by family, sample suffix (`_sum`, `_count` or empty) and labels.
+    series: Mutex<BTreeMap<(&'static str, &'static str, Labels), f64>>,
+}
+
+/// The registry the bot records into.
+pub static METRICS: Registry = Registry::new();
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    pub const fn new() -> Self {
+        Self {
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn add(
+        &self,
+        family: &'static str,
+        suffix: &'static str,
+        labels: &[(&'static str, &str)],
+        value: f64,
+    ) {
+        let labels = labels
+            .iter()
+            .map(|(name, value)| (*name, value.to_string()))
+            .collect();
+        let mut series = self.series.lock().expect("Metrics lock poisoned");
+        *series.entry((family, suffix, labels)).or_default() += value;
+    }
+
+    pub fn increment(&self, family: &'static str, labels: &[(&'static str, &str)]) {
+        self.add(family, "", labels, 1.0);
+    }
+
+    /// Adds one observation to a summary's `_sum` and `_count`.
+    pub fn observe(&self, family: &'static str, labels: &[(&'static str, &str)], value: f64) {
+        self.add(family, "_sum", labels, value);
+        self.add(family, "_count", labels, 1.0);
+    }
+
+    pub fn get(&self, family: &'static str, labels: &[(&'static str, &str)]) -> f64 {
+        let labels: Labels = labels
+            .iter()
+            .map(|(name, value)| (*name, value.to_string()))
+            .collect();
+        let series = self.series.lock().expect("Metrics lock poisoned");
+        series
+            .get(&(family, "", labels))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn render(&self) -> String {
+        let series = self.series.lock().expect("Metrics lock poisoned");
+        let mut out = String::new();
+        for (family, kind, help) in FAMILIES {
+            header(&mut out, family, kind, help);
+            for ((_, suffix, labels), value) in series.iter().filter(|((f, _, _), _)| f == family) {
+                sample(&mut out, &format!("{}{}", family, suffix), labels, *value);
+            }
+        }
+        out
+    }
+}
+
+pub(crate) fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+pub(crate) fn sample(out: &mut String, name: &str, labels: &[(&str, String)], value: f64) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels = labels
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        let _ = write!(out, "{{{}}}", labels);
+    }
+    let _ = writeln!(out, " {}", value);
+}
+
+/// Label values may contain anything, the format only needs these escaped.
+pub fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// `kind` is prefix, custom or slash, `outcome` the same value the command log uses.
+pub fn command(kind: &str, command: &str, outcome: &str) {
+    METRICS.increment(
+        "ronnbot_commands_total",
+        &[
+            ("kind", kind),
+            ("command", &command.to_lowercase()),
+            ("outcome", outcome),
+        ],
+    );
+}
+
+pub fn api_request(host: &str, elapsed: Duration, error: Option<&str>) {
+    METRICS.observe(
+        "ronnbot_api_request_seconds",
+        &[("host", host)],
+        elapsed.as_secs_f64(),
+    );
+    if let Some(error) = error {
+        METRICS.increment(
+            "ronnbot_api_errors_total",
+            &[("host", host), ("error", error)],
+        );
+    }
+}
+
+pub fn reaction_role(action: &str, success: bool) {
+    let outcome = if success { "ok" } else { "error" };
+    METRICS.increment(
+        "ronnbot_reaction_roles_total",
+        &[("action", action), ("outcome", outcome)],
+    );
+}
+
+pub fn cache_lookup(cache: &str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    METRICS.increment(
+        "ronnbot_cache_requests_total",
+        &[("cache", cache), ("result", result)],
+    );
+}
@@ -0,0 +1,155 @@
+use super::{header, sample, METRICS};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header::CONTENT_TYPE, Body, Method, Request, Response, Server, StatusCode};
+use serde::Serialize;
+use serenity::client::bridge::gateway::ShardManager;
+use serenity::gateway::ConnectionStage;
+use serenity::prelude::Mutex;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{error, info};
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShardHealth {
+    pub id: u64,
+    pub stage: String,
+    pub latency_ms: Option<u64>,
+}
+
+impl ShardHealth {
+    pub fn connected(&self) -> bool {
+        self.stage == ConnectionStage::Connected.to_string()
+    }
+}
+
+/// What `/healthz` reports, one entry per shard the manager runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Health {
+    pub shards: Vec<ShardHealth>,
+}
+
+impl Health {
+    pub async fn collect(shard_manager: &Mutex<ShardManager>) -> Self {
+        let runners = shard_manager.lock().await.runners.clone();
+        let runners = runners.lock().await;
+        let mut shards: Vec<_> = runners
+            .iter()
+            .map(|(id, runner)| ShardHealth {
+                id: id.0,
+                stage: runner.stage.to_string(),
+                latency_ms: runner.latency.map(|latency| latency.as_millis() as u64),
+            })
+            .collect();
+        shards.sort_by_key(|shard| shard.id);
+        Self { shards }
+    }
+
+    /// Healthy once every shard is connected, so not while the client is still starting.
+    pub fn is_healthy(&self) -> bool {
+        !self.shards.is_empty() && self.shards.iter().all(ShardHealth::connected)
+    }
+
+    pub fn status(&self) -> StatusCode {
+        if self.is_healthy() {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+    }
+
+    /// Shard gauges in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        header(
+            &mut out,
+            "ronnbot_shard_connected",
+            "gauge",
+            "Whether the shard is connected to the gateway.",
+        );
+        for shard in &self.shards {
+            let labels = [("shard", shard.id.to_string())];
+            let connected = if shard.connected() { 1.0 } else { 0.0 };
+            sample(&mut out, "ronnbot_shard_connected", &labels, connected);
+        }
+        header(
+            &mut out,
+            "ronnbot_gateway_latency_seconds",
+            "gauge",
+            "Last heartbeat round trip to the gateway.",
+        );
+        for shard in &self.shards {
+            if let Some(latency_ms) = shard.latency_ms {
+                let labels = [("shard", shard.id.to_string())];
+                let seconds = latency_ms as f64 / 1000.0;
+                sample(
+                    &mut out,
+                    "ronnbot_gateway_latency_seconds",
+                    &labels,
+                    seconds,
+                );
+            }
+        }
+        out
+    }
+}
+
+async fn handle(
+    request: Request<Body>,
+    shard_manager: Arc<Mutex<ShardManager>>,
+) -> Result<Response<Body>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/healthz") => {
+            let health = Health::collect(&shard_manager).await;
+            Response::builder()
+                .status(health.status())
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    serde_json::to_string(&health).unwrap_or_default(),
+                ))
+        }
+        (&Method::GET, "/metrics") => {
+            let health = Health::collect(&shard_manager).await;
+            Response::builder()
+                .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+                .body(Body::from(METRICS.render() + &health.render()))
+        }
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+    };
+    Ok(response.expect("Static response parts are valid"))
+}
+
+pub async fn serve(addr: SocketAddr, shard_manager: Arc<Mutex<ShardManager>>) -> hyper::Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let shard_manager = shard_manager.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle(request, shard_manager.clone())
+            }))
+        }
+    });
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    info!(%addr, "Serving /healthz and /metrics");
+    server.await
+}
+
+/// Runs the health and metrics server next to the client, `addr` defaults to [`DEFAULT_ADDR`].
+pub fn start(addr: Option<&str>, shard_manager: Arc<Mutex<ShardManager>>) {
+    let addr = addr.unwrap_or(DEFAULT_ADDR);
+    let addr: SocketAddr = match addr.parse() {
+        Ok(addr) => addr,
+        Err(why) => {
+            error!(addr, "Invalid HTTP address, not serving metrics: {}", why);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        if let Err(why) = serve(addr, shard_manager).await {
+            error!(%addr, "Health server stopped: {}", why);
+        }
+    });
+}
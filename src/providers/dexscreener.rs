@@ -0,0 +1,44 @@
+use super::TokenPriceProvider;
+use crate::ronn_utils::apiclient::{ApiClient, ApiError};
+use crate::slashcommands::coin::Root;
+use reqwest::header::HeaderMap;
+use reqwest::Url;
+use serenity::async_trait;
+
+pub const NAME: &str = "dexscreener";
+
+pub struct DexScreener {
+    base_url: String,
+}
+
+pub const BASE_URL: &str = "https://api.dexscreener.com";
+
+impl Default for DexScreener {
+    fn default() -> Self {
+        Self::with_base_url(BASE_URL)
+    }
+}
+
+impl DexScreener {
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenPriceProvider for DexScreener {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    async fn search(&self, client: &ApiClient, query: &str) -> Result<Root, ApiError> {
+        let url = Url::parse_with_params(
+            &format!("{}/latest/dex/search", self.base_url),
+            &[("q", query)],
+        )
+        .map_err(|why| ApiError::Unreachable(why.to_string()))?;
+        client.get_json(url.as_str(), HeaderMap::new()).await
+    }
+}
@@ -0,0 +1,177 @@
+use super::TokenPriceProvider;
+use crate::ronn_utils::apiclient::{ApiClient, ApiError};
+use crate::slashcommands::coin::{
+    BaseToken, Liquidity, Pair, PriceChange, QuoteToken, Root, Volume,
+};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
+use reqwest::Url;
+use serde::Deserialize;
+use serenity::async_trait;
+
+pub const NAME: &str = "geckoterminal";
+
+pub struct GeckoTerminal {
+    base_url: String,
+}
+
+pub const BASE_URL: &str = "https://api.geckoterminal.com/api/v2";
+
+impl Default for GeckoTerminal {
+    fn default() -> Self {
+        Self::with_base_url(BASE_URL)
+    }
+}
+
+impl GeckoTerminal {
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    data: Vec<Pool>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct Pool {
+    /// `{network}_{pool address}`
+    id: String,
+    attributes: PoolAttributes,
+    #[serde(default)]
+    relationships: PoolRelationships,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct PoolAttributes {
+    address: String,
+    name: String,
+    base_token_price_usd: Option<String>,
+    base_token_price_quote_token: Option<String>,
+    reserve_in_usd: Option<String>,
+    #[serde(default)]
+    price_change_percentage: Timeframes,
+    #[serde(default)]
+    volume_usd: Timeframes,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct Timeframes {
+    m5: Option<String>,
+    h1: Option<String>,
+    h6: Option<String>,
+    h24: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default)]
+struct PoolRelationships {
+    base_token: Relationship,
+    quote_token: Relationship,
+    dex: Relationship,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct Relationship {
+    data: RelationshipData,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct RelationshipData {
+    id: String,
+}
+
+fn number(value: &Option<String>) -> f64 {
+    value
+        .as_deref()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Token ids look like `eth_0xabc`, the address is everything after the network.
+fn address(id: &str) -> String {
+    id.split_once('_')
+        .map_or(id, |(_, address)| address)
+        .to_string()
+}
+
+impl From<Pool> for Pair {
+    fn from(pool: Pool) -> Self {
+        let network = pool
+            .id
+            .split_once('_')
+            .map_or("", |(network, _)| network)
+            .to_string();
+        let attributes = pool.attributes;
+        // Pool names are `BASE / QUOTE`, optionally followed by a fee tier
+        let mut symbols = attributes.name.split(" / ");
+        let base_symbol = symbols.next().unwrap_or_default().trim().to_string();
+        let quote_symbol = symbols
+            .next()
+            .and_then(|s| s.split_whitespace().next())
+            .unwrap_or_default()
+            .to_string();
+        Pair {
+            url: format!(
+                "https://www.geckoterminal.com/{}/pools/{}",
+                network, attributes.address
+            ),
+            chain_id: network,
+            dex_id: pool.relationships.dex.data.id,
+            pair_address: attributes.address,
+            base_token: BaseToken {
+                address: address(&pool.relationships.base_token.data.id),
+                name: base_symbol.clone(),
+                symbol: base_symbol,
+            },
+            quote_token: QuoteToken {
+                address: address(&pool.relationships.quote_token.data.id),
+                name: quote_symbol.clone(),
+                symbol: quote_symbol,
+            },
+            price_native: attributes.base_token_price_quote_token.unwrap_or_default(),
+            price_usd: attributes.base_token_price_usd,
+            volume: Volume {
+                h24: number(&attributes.volume_usd.h24),
+                h6: number(&attributes.volume_usd.h6),
+                h1: number(&attributes.volume_usd.h1),
+                m5: number(&attributes.volume_usd.m5),
+            },
+            price_change: PriceChange {
+                h24: number(&attributes.price_change_percentage.h24),
+                h6: number(&attributes.price_change_percentage.h6),
+                h1: number(&attributes.price_change_percentage.h1),
+                m5: number(&attributes.price_change_percentage.m5),
+            },
+            liquidity: attributes.reserve_in_usd.as_ref().map(|_| Liquidity {
+                usd: number(&attributes.reserve_in_usd),
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenPriceProvider for GeckoTerminal {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    async fn search(&self, client: &ApiClient, query: &str) -> Result<Root, ApiError> {
+        let url = Url::parse_with_params(
+            &format!("{}/search/pools", self.base_url),
+            &[("query", query)],
+        )
+        .map_err(|why| ApiError::Unreachable(why.to_string()))?;
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        let response: SearchResponse = client.get_json(url.as_str(), headers).await?;
+        Ok(Root {
+            schema_version: NAME.to_string(),
+            pairs: response.data.into_iter().map(Pair::from).collect(),
+        })
+    }
+}
@@ -0,0 +1,236 @@
+use crate::data::config::ProviderConfig;
+use crate::metrics;
+use crate::ronn_utils::apiclient::{ApiClient, ApiError};
+use crate::ronn_utils::cache::{Cached, TtlCache};
+use crate::slashcommands::{coin, floorprice};
+use serenity::async_trait;
+use serenity::model::id::GuildId;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::warn;
+
+pub mod dexscreener;
+pub mod geckoterminal;
+pub mod opensea;
+pub mod reservoir;
+
+/// Looks up token pairs by free text search.
+#[async_trait]
+pub trait TokenPriceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn search(&self, client: &ApiClient, query: &str) -> Result<coin::Root, ApiError>;
+}
+
+/// Looks up NFT collections and their floor by collection name.
+#[async_trait]
+pub trait NftFloorProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn collections(
+        &self,
+        client: &ApiClient,
+        name: &str,
+    ) -> Result<floorprice::Root, ApiError>;
+}
+
+/// Lets the lookup chain treat both kinds of provider alike.
+trait Named {
+    fn name(&self) -> &'static str;
+}
+
+impl Named for dyn TokenPriceProvider {
+    fn name(&self) -> &'static str {
+        TokenPriceProvider::name(self)
+    }
+}
+
+impl Named for dyn NftFloorProvider {
+    fn name(&self) -> &'static str {
+        NftFloorProvider::name(self)
+    }
+}
+
+/// Every registered market data provider plus the shared client and caches.
+/// Lookups walk the guild's provider chain and fall through on errors or empty results.
+pub struct MarketData {
+    pub client: Arc<ApiClient>,
+    tokens: Vec<Arc<dyn TokenPriceProvider>>,
+    nfts: Vec<Arc<dyn NftFloorProvider>>,
+    token_cache: TtlCache<coin::Root>,
+    nft_cache: TtlCache<floorprice::Root>,
+    config: RwLock<ProviderConfig>,
+}
+
+impl MarketData {
+    pub fn new(
+        client: Arc<ApiClient>,
+        tokens: Vec<Arc<dyn TokenPriceProvider>>,
+        nfts: Vec<Arc<dyn NftFloorProvider>>,
+        config: ProviderConfig,
+    ) -> Self {
+        let market = Self {
+            client,
+            tokens,
+            nfts,
+            token_cache: TtlCache::new(Duration::from_secs(60), Duration::from_secs(60 * 60)),
+            nft_cache: TtlCache::new(
+                Duration::from_secs(5 * 60),
+                Duration::from_secs(6 * 60 * 60),
+            ),
+            config: RwLock::new(ProviderConfig::default()),
+        };
+        market.set_config(config);
+        market
+    }
+
+    /// Swaps the provider preferences, cached answers are kept.
+    pub fn set_config(&self, config: ProviderConfig) {
+        let known: Vec<&str> = self
+            .tokens
+            .iter()
+            .map(|p| p.name())
+            .chain(self.nfts.iter().map(|p| p.name()))
+            .collect();
+        let configured = config.tokens.iter().chain(config.nfts.iter()).chain(
+            config
+                .guilds
+                .values()
+                .flat_map(|g| g.tokens.iter().chain(g.nfts.iter())),
+        );
+        for name in configured {
+            if !known.contains(&name.as_str()) {
+                warn!(
+                    "Unknown market data provider '{}' in config, ignoring it",
+                    name
+                );
+            }
+        }
+        *self.config.write().expect("Provider config lock poisoned") = config;
+    }
+
+    fn config(&self) -> ProviderConfig {
+        self.config
+            .read()
+            .expect("Provider config lock poisoned")
+            .clone()
+    }
+
+    /// Guild preference first, then the global order, then anything else registered.
+    fn chain<'a, P: Named + ?Sized>(
+        providers: &'a [Arc<P>],
+        preferred: &[&Vec<String>],
+    ) -> Vec<&'a Arc<P>> {
+        let mut chain: Vec<&Arc<P>> = vec![];
+        let wanted = preferred.iter().flat_map(|names| names.iter());
+        for provider_name in wanted {
+            if let Some(provider) = providers.iter().find(|p| p.name() == provider_name) {
+                if !chain.iter().any(|p| Arc::ptr_eq(p, provider)) {
+                    chain.push(provider);
+                }
+            }
+        }
+        for provider in providers {
+            if !chain.iter().any(|p| Arc::ptr_eq(p, provider)) {
+                chain.push(provider);
+            }
+        }
+        chain
+    }
+
+    /// Walks the chain and returns the first non-empty answer, cached or fetched.
+    /// When every provider fails or comes back empty, a stale cached answer beats an
+    /// empty one, which beats the last error.
+    async fn lookup<'a, P, T, Fut>(
+        kind: &'static str,
+        providers: &'a [Arc<P>],
+        preferred: &[&Vec<String>],
+        cache: &TtlCache<T>,
+        query: &str,
+        is_empty: impl Fn(&T) -> bool,
+        fetch: impl Fn(&'a Arc<P>) -> Fut,
+    ) -> Result<Cached<T>, ApiError>
+    where
+        P: Named + ?Sized,
+        T: Clone,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let chain = Self::chain(providers, preferred);
+        let normalised = TtlCache::<T>::normalise(query);
+
+        let mut last_error = ApiError::NotFound;
+        let mut empty = None;
+        for provider in &chain {
+            let key = format!("{}:{}", provider.name(), normalised);
+            let cached = cache.fresh(&key);
+            metrics::cache_lookup(kind, cached.is_some());
+            let result = match cached {
+                Some(cached) => Ok(cached),
+                None => fetch(provider).await.map(|value| cache.insert(&key, value)),
+            };
+            match result {
+                Ok(cached) if !is_empty(&cached.value) => return Ok(cached),
+                Ok(cached) => empty = empty.or(Some(cached)),
+                Err(why) => {
+                    warn!(kind, "Provider {} failed: {}", provider.name(), why);
+                    last_error = why;
+                }
+            }
+        }
+        chain
+            .iter()
+            .find_map(|provider| cache.stale(&format!("{}:{}", provider.name(), normalised)))
+            .or(empty)
+            .ok_or(last_error)
+    }
+
+    pub async fn search_tokens(
+        &self,
+        guild_id: Option<GuildId>,
+        query: &str,
+    ) -> Result<Cached<coin::Root>, ApiError> {
+        let config = self.config();
+        let guild = guild_id.and_then(|id| config.guilds.get(&id.0));
+        let mut preferred = vec![];
+        if let Some(guild) = guild {
+            preferred.push(&guild.tokens);
+        }
+        preferred.push(&config.tokens);
+        Self::lookup(
+            "tokens",
+            &self.tokens,
+            &preferred,
+            &self.token_cache,
+            query,
+            |root| root.pairs.is_empty(),
+            |provider| provider.search(&self.client, query),
+        )
+        .await
+    }
+
+    pub async fn search_collections(
+        &self,
+        guild_id: Option<GuildId>,
+        name: &str,
+    ) -> Result<Cached<floorprice::Root>, ApiError> {
+        let config = self.config();
+        let guild = guild_id.and_then(|id| config.guilds.get(&id.0));
+        let mut preferred = vec![];
+        if let Some(guild) = guild {
+            preferred.push(&guild.nfts);
+        }
+        preferred.push(&config.nfts);
+        if self.nfts.is_empty() {
+            return Err(ApiError::NotConfigured("NFT floor price lookup"));
+        }
+        Self::lookup(
+            "nfts",
+            &self.nfts,
+            &preferred,
+            &self.nft_cache,
+            name,
+            |root| root.collections.is_empty(),
+            |provider| provider.collections(&self.client, name),
+        )
+        .await
+    }
+}
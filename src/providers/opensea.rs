@@ -0,0 +1,137 @@
+use super::NftFloorProvider;
+use crate::ronn_utils::apiclient::{ApiClient, ApiError};
+use crate::slashcommands::floorprice::{
+    Amount, Collection, CollectionVolume, FloorAsk, Price, Root,
+};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT};
+use serde::Deserialize;
+use serenity::async_trait;
+use tracing::debug;
+
+pub const NAME: &str = "opensea";
+
+pub struct OpenSea {
+    base_url: String,
+    api_key: String,
+}
+
+pub const BASE_URL: &str = "https://api.opensea.io/api/v2";
+
+impl OpenSea {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(BASE_URL, api_key)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>, api_key: String) -> Self {
+        Self {
+            base_url: base_url.into(),
+            api_key,
+        }
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Ok(value) = HeaderValue::from_str(self.api_key.as_str()) {
+            headers.insert(HeaderName::from_static("x-api-key"), value);
+        }
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        headers
+    }
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct CollectionResponse {
+    name: String,
+    #[serde(default)]
+    image_url: Option<String>,
+    #[serde(default)]
+    contracts: Vec<Contract>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct Contract {
+    address: String,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct StatsResponse {
+    total: Total,
+    #[serde(default)]
+    intervals: Vec<Interval>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct Total {
+    floor_price: Option<f64>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize)]
+struct Interval {
+    interval: String,
+    volume: Option<f64>,
+}
+
+/// OpenSea looks collections up by slug, which is the name without spaces for most of them.
+pub fn slug(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[async_trait]
+impl NftFloorProvider for OpenSea {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    async fn collections(&self, client: &ApiClient, name: &str) -> Result<Root, ApiError> {
+        let slug = slug(name);
+        if slug.is_empty() {
+            return Ok(Root::default());
+        }
+        let url = format!("{}/collections/{}", self.base_url, slug);
+        debug!(%url, "Fetching collection");
+        let collection: CollectionResponse = match client.get_json(&url, self.headers()).await {
+            Ok(collection) => collection,
+            // Unknown slugs are not an outage, report no collection like the others do
+            Err(ApiError::NotFound) => return Ok(Root::default()),
+            Err(why) => return Err(why),
+        };
+        let stats: StatsResponse = client
+            .get_json(&format!("{}/stats", url), self.headers())
+            .await?;
+        let floor = stats.total.floor_price;
+        let one_day = stats
+            .intervals
+            .iter()
+            .find(|interval| interval.interval == "one_day")
+            .and_then(|interval| interval.volume);
+        Ok(Root {
+            collections: vec![Collection {
+                name: collection.name,
+                floor_ask: FloorAsk {
+                    source_domain: "opensea.io".to_string(),
+                    price: floor.map(|floor| Price {
+                        amount: Amount {
+                            decimal: floor,
+                            native: floor,
+                            ..Default::default()
+                        },
+                    }),
+                },
+                primary_contract: collection
+                    .contracts
+                    .into_iter()
+                    .next()
+                    .map(|contract| contract.address)
+                    .unwrap_or_default(),
+                image: collection.image_url.unwrap_or_default(),
+                volume: one_day.map(|volume| CollectionVolume {
+                    one_day: Some(volume),
+                }),
+                floor_sale_change: None,
+            }],
+        })
+    }
+}
@@ -0,0 +1,56 @@
+use super::NftFloorProvider;
+use crate::ronn_utils::apiclient::{ApiClient, ApiError};
+use crate::slashcommands::floorprice::Root;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Url;
+use serenity::async_trait;
+use tracing::debug;
+
+pub const NAME: &str = "reservoir";
+
+pub struct Reservoir {
+    base_url: String,
+    api_key: String,
+}
+
+pub const BASE_URL: &str = "https://api.reservoir.tools";
+
+impl Reservoir {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(BASE_URL, api_key)
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>, api_key: String) -> Self {
+        Self {
+            base_url: base_url.into(),
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl NftFloorProvider for Reservoir {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    async fn collections(&self, client: &ApiClient, name: &str) -> Result<Root, ApiError> {
+        let url = Url::parse_with_params(
+            &format!("{}/collections/v6", self.base_url),
+            &[("name", name)],
+        )
+        .map_err(|why| ApiError::Unreachable(why.to_string()))?;
+        debug!(%url, "Fetching collection");
+
+        let mut headers = HeaderMap::new();
+        if let Ok(value) = HeaderValue::from_str(self.api_key.as_str()) {
+            headers.insert(HeaderName::from_static("x-api-key"), value);
+        }
+        headers.insert(
+            HeaderName::from_static("accept"),
+            HeaderValue::from_static("*/*"),
+        );
+
+        client.get_json(url.as_str(), headers).await
+    }
+}
@@ -0,0 +1,53 @@
+use serenity::http::Http;
+use serenity::model::guild::Member;
+use serenity::model::id::RoleId;
+use tracing::{info, warn};
+
+/// Gives or takes one role and logs the outcome, returns whether Discord accepted it.
+/// Used by reaction roles.
+pub async fn update(
+    http: impl AsRef<Http>,
+    member: &mut Member,
+    role_id: RoleId,
+    add: bool,
+) -> bool {
+    let result = if add {
+        member.add_role(http, role_id).await
+    } else {
+        member.remove_role(http, role_id).await
+    };
+    match (result, add) {
+        (Ok(()), true) => info!(role = role_id.0, user = member.user.id.0, "Role added"),
+        (Ok(()), false) => info!(role = role_id.0, user = member.user.id.0, "Role removed"),
+        (Err(why), true) => {
+            warn!(role = role_id.0, "Role could not be added: {}", why);
+            return false;
+        }
+        (Err(why), false) => {
+            warn!(role = role_id.0, "Role could not be removed: {}", why);
+            return false;
+        }
+    }
+    true
+}
+
+/// Gives several roles in one request and logs the outcome, returns Discord's error so
+/// the caller can tell a member who left from a failure worth retrying. Used for the
+/// roles given on join.
+pub async fn add_all(
+    http: impl AsRef<Http>,
+    member: &mut Member,
+    role_ids: &[RoleId],
+) -> serenity::Result<()> {
+    let roles: Vec<u64> = role_ids.iter().map(|role_id| role_id.0).collect();
+    match member.add_roles(http, role_ids).await {
+        Ok(_) => {
+            info!(?roles, user = member.user.id.0, "Roles added");
+            Ok(())
+        }
+        Err(why) => {
+            warn!(?roles, "Roles could not be added: {}", why);
+            Err(why)
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use crate::metrics;
+use chrono::{DateTime, Utc};
+use hyper::client::connect::dns::Name;
+use rand::Rng;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::redirect::Policy;
+use reqwest::{Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const BASE_DELAY: Duration = Duration::from_millis(500);
+/// Never sleep longer than this between attempts, a longer `Retry-After` fails fast instead.
+const MAX_DELAY: Duration = Duration::from_secs(15);
+const MAX_RETRIES: u32 = 3;
+const MAX_REDIRECTS: usize = 5;
+/// Hosts with their own metrics label, everything else is `other`.
+pub const KNOWN_HOSTS: &[&str] = &[
+    "api.dexscreener.com",
+    "api.geckoterminal.com",
+    "api.reservoir.tools",
+    "api.opensea.io",
+    "cdn.discordapp.com",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    Unreachable(String),
+    Timeout,
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
+    NotFound,
+    Status(StatusCode),
+    Decode(String),
+    /// The body was bigger than the caller allows, in bytes.
+    TooLarge(u64),
+    /// No provider is set up for this kind of data, usually for lack of an API key.
+    NotConfigured(&'static str),
+    /// A user supplied URL points somewhere the bot must not fetch from.
+    Blocked(String),
+}
+
+/// Reads a `Retry-After` header, either whole seconds or an HTTP-date. A date in the
+/// past means no wait.
+pub fn retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Unreachable(why) => write!(f, "API cannot be reached ({})", why),
+            ApiError::Timeout => write!(f, "API took too long to answer"),
+            ApiError::RateLimited {
+                retry_after: Some(after),
+            } => write!(f, "API is rate limited, try again in {}s", after.as_secs()),
+            ApiError::RateLimited { retry_after: None } => write!(f, "API is rate limited"),
+            ApiError::NotFound => write!(f, "Nothing was found"),
+            ApiError::Status(status) => write!(f, "API answered with {}", status),
+            ApiError::Decode(why) => write!(f, "API answer could not be parsed ({})", why),
+            ApiError::TooLarge(limit) => write!(f, "File is larger than {} KB", limit / 1024),
+            ApiError::NotConfigured(what) => write!(f, "{} is not configured on this bot", what),
+            ApiError::Blocked(why) => write!(f, "URL is not allowed ({})", why),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ApiError {
+    fn from_reqwest(why: reqwest::Error) -> Self {
+        if why.is_timeout() {
+            ApiError::Timeout
+        } else {
+            ApiError::Unreachable(why.to_string())
+        }
+    }
+
+    fn from_response(response: &Response) -> Self {
+        match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited {
+                retry_after: response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| retry_after(value, Utc::now())),
+            },
+            StatusCode::NOT_FOUND => ApiError::NotFound,
+            status => ApiError::Status(status),
+        }
+    }
+
+    /// Short name for the metrics label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ApiError::Unreachable(_) => "unreachable",
+            ApiError::Timeout => "timeout",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::NotFound => "not_found",
+            ApiError::Status(_) => "status",
+            ApiError::Decode(_) => "decode",
+            ApiError::TooLarge(_) => "too_large",
+            ApiError::NotConfigured(_) => "not_configured",
+            ApiError::Blocked(_) => "blocked",
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::Unreachable(_) | ApiError::Timeout | ApiError::RateLimited { .. } => true,
+            ApiError::Status(status) => status.is_server_error(),
+            ApiError::NotFound
+            | ApiError::Decode(_)
+            | ApiError::TooLarge(_)
+            | ApiError::NotConfigured(_)
+            | ApiError::Blocked(_) => false,
+        }
+    }
+}
+
+/// Whether `ip` is on the public internet. Downloads of user supplied URLs may only
+/// reach these, never loopback, private, link-local or unique-local networks.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // Carrier-grade NAT
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local fc00::/7 and link-local fe80::/10
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80)
+        }
+    }
+}
+
+/// Only https, and IP literals must be public. Hostnames are checked when they resolve.
+pub fn check_download_url(url: &Url) -> Result<(), ApiError> {
+    if url.scheme() != "https" {
+        return Err(ApiError::Blocked("only https URLs can be used".to_string()));
+    }
+    match ip_literal(url)? {
+        Some(ip) if !is_public(ip) => {
+            Err(ApiError::Blocked(format!("{} is not a public address", ip)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The host of `url` when it is an IP address rather than a name.
+fn ip_literal(url: &Url) -> Result<Option<IpAddr>, ApiError> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| ApiError::Blocked("the URL has no host".to_string()))?;
+    Ok(host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok())
+}
+
+/// Resolves `host`, refusing it when any of its addresses is not public.
+async fn public_addrs(host: &str) -> Result<Vec<SocketAddr>, ApiError> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|why| ApiError::Unreachable(why.to_string()))?
+        .collect();
+    if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+        return Err(ApiError::Blocked(format!(
+            "{} resolves to {}, which is not a public address",
+            host,
+            addr.ip()
+        )));
+    }
+    if addrs.is_empty() {
+        return Err(ApiError::Unreachable(format!("{} does not resolve", host)));
+    }
+    Ok(addrs)
+}
+
+/// Checks every connection of the download client, including redirects and later
+/// lookups of the same host, so DNS cannot swap in a private address after the check.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = public_addrs(name.as_str()).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Shared HTTP client for the market data APIs and downloads. Reuses connections and
+/// retries transient failures with jittered exponential backoff.
+pub struct ApiClient {
+    client: reqwest::Client,
+    /// For user supplied URLs, only reaches public https hosts.
+    downloads: reqwest::Client,
+    timeouts: HashMap<String, Duration>,
+}
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        let mut timeouts = HashMap::new();
+        timeouts.insert("api.dexscreener.com".to_string(), Duration::from_secs(5));
+        timeouts.insert("api.reservoir.tools".to_string(), Duration::from_secs(10));
+        timeouts.insert("api.opensea.io".to_string(), Duration::from_secs(10));
+        Self {
+            client: reqwest::Client::builder()
+                .user_agent("ronnbot")
+                .connect_timeout(Duration::from_secs(5))
+                .pool_idle_timeout(Duration::from_secs(90))
+                .build()
+                .expect("Could not build HTTP client"),
+            downloads: reqwest::Client::builder()
+                .user_agent("ronnbot")
+                .connect_timeout(Duration::from_secs(5))
+                .https_only(true)
+                .dns_resolver(Arc::new(PublicResolver))
+                .redirect(Policy::custom(|attempt| {
+                    if attempt.previous().len() >= MAX_REDIRECTS {
+                        return attempt.error("too many redirects");
+                    }
+                    match check_download_url(attempt.url()) {
+                        Ok(()) => attempt.follow(),
+                        Err(why) => attempt.error(why),
+                    }
+                }))
+                .build()
+                .expect("Could not build HTTP client"),
+            timeouts,
+        }
+    }
+}
+
+impl ApiClient {
+    fn timeout_for(&self, url: &str) -> Duration {
+        Url::parse(url)
+            .ok()
+            .and_then(|url| {
+                url.host_str()
+                    .and_then(|host| self.timeouts.get(host))
+                    .copied()
+            })
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    /// The metrics label for a URL's host. Hosts outside [`KNOWN_HOSTS`] share `other`,
+    /// so user supplied URLs cannot grow the label set.
+    pub fn host(url: &str) -> &'static str {
+        Url::parse(url)
+            .ok()
+            .and_then(|url| {
+                url.host_str()
+                    .and_then(|host| KNOWN_HOSTS.iter().find(|known| **known == host))
+                    .copied()
+            })
+            .unwrap_or("other")
+    }
+
+    /// Full jitter: a random delay between zero and `BASE_DELAY * 2^attempt`.
+    fn backoff(attempt: u32) -> Duration {
+        let ceiling = BASE_DELAY
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_DELAY);
+        rand::thread_rng().gen_range(Duration::ZERO..=ceiling)
+    }
+
+    /// Sends a GET, retrying transient failures, and returns the successful response.
+    async fn get(&self, url: &str, headers: HeaderMap) -> Result<Response, ApiError> {
+        self.get_with(&self.client, url, headers).await
+    }
+
+    async fn get_with(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        headers: HeaderMap,
+    ) -> Result<Response, ApiError> {
+        let timeout = self.timeout_for(url);
+        let host = Self::host(url);
+        let mut attempt = 0;
+        loop {
+            let started = Instant::now();
+            let result = client
+                .get(url)
+                .headers(headers.clone())
+                .timeout(timeout)
+                .send()
+                .await;
+            let error = match result {
+                Ok(response) if response.status().is_success() => {
+                    metrics::api_request(host, started.elapsed(), None);
+                    return Ok(response);
+                }
+                Ok(response) => ApiError::from_response(&response),
+                Err(why) => ApiError::from_reqwest(why),
+            };
+            metrics::api_request(host, started.elapsed(), Some(error.kind()));
+            if !error.is_retryable() || attempt >= MAX_RETRIES {
+                return Err(error);
+            }
+            let delay = match &error {
+                ApiError::RateLimited {
+                    retry_after: Some(after),
+                } => *after,
+                _ => Self::backoff(attempt),
+            };
+            if delay > MAX_DELAY {
+                return Err(error);
+            }
+            debug!(
+                "Request to {} failed ({}), retrying in {}ms",
+                url,
+                error,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+    ) -> Result<T, ApiError> {
+        let body = self
+            .get(url, headers)
+            .await?
+            .bytes()
+            .await
+            .map_err(ApiError::from_reqwest)?;
+        serde_json::from_slice(&body).map_err(|why| ApiError::Decode(why.to_string()))
+    }
+
+    /// Downloads a file into memory, giving up as soon as it grows past `max_bytes`.
+    pub async fn get_bytes(&self, url: &str, max_bytes: u64) -> Result<Vec<u8>, ApiError> {
+        let response = self.get(url, HeaderMap::new()).await?;
+        Self::read_limited(response, max_bytes).await
+    }
+
+    /// Same as [`ApiClient::get_bytes`] for a URL a user gave. Only public https hosts
+    /// are reached, redirects included.
+    pub async fn download(&self, url: &str, max_bytes: u64) -> Result<Vec<u8>, ApiError> {
+        let parsed = Url::parse(url).map_err(|why| ApiError::Blocked(why.to_string()))?;
+        check_download_url(&parsed)?;
+        // Checked again on connect, this only gives a clearer error than the resolver's
+        if let (Some(host), None) = (parsed.host_str(), ip_literal(&parsed)?) {
+            public_addrs(host).await?;
+        }
+        let response = self
+            .get_with(&self.downloads, url, HeaderMap::new())
+            .await?;
+        Self::read_limited(response, max_bytes).await
+    }
+
+    async fn read_limited(mut response: Response, max_bytes: u64) -> Result<Vec<u8>, ApiError> {
+        if response.content_length().is_some_and(|len| len > max_bytes) {
+            return Err(ApiError::TooLarge(max_bytes));
+        }
+        let mut body = vec![];
+        while let Some(chunk) = response.chunk().await.map_err(ApiError::from_reqwest)? {
+            if (body.len() + chunk.len()) as u64 > max_bytes {
+                return Err(ApiError::TooLarge(max_bytes));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Index of the files in the static images folder, built once at startup.
+/// Only indexed files can be read, so a bad name never reaches the filesystem.
+#[derive(Default, Debug, Clone)]
+pub struct Assets {
+    root: PathBuf,
+    /// Filename to size in bytes.
+    files: BTreeMap<String, u64>,
+}
+
+impl Assets {
+    /// A missing or unreadable folder gives an empty index rather than an error,
+    /// the bot still works without images.
+    pub fn index(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let mut files = BTreeMap::new();
+        match std::fs::read_dir(&root) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    let Ok(metadata) = entry.metadata() else {
+                        continue;
+                    };
+                    if let (true, Some(name)) = (metadata.is_file(), entry.file_name().to_str()) {
+                        files.insert(name.to_string(), metadata.len());
+                    }
+                }
+            }
+            Err(why) => warn!(
+                "Images folder {} could not be read: {}",
+                root.display(),
+                why
+            ),
+        }
+        info!("Indexed {} assets in {}", files.len(), root.display());
+        Self { root, files }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.files.contains_key(name)
+    }
+
+    /// Filenames and sizes, sorted by name.
+    pub fn files(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.files.iter().map(|(name, size)| (name.as_str(), *size))
+    }
+
+    /// The referenced names that are not in the folder, sorted and deduplicated.
+    pub fn missing<'a>(&self, referenced: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut missing: Vec<String> = referenced
+            .into_iter()
+            .filter(|name| !self.contains(name))
+            .map(str::to_string)
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        missing
+    }
+
+    pub async fn read(&self, name: &str) -> std::io::Result<Vec<u8>> {
+        if !self.contains(name) {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("image `{}` is missing", name),
+            ));
+        }
+        tokio::fs::read(self.root.join(name)).await
+    }
+}
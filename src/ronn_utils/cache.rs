@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const MAX_ENTRIES: usize = 500;
+
+/// A value served from a [`TtlCache`], with when it was fetched upstream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cached<T> {
+    pub value: T,
+    pub fetched_at: DateTime<Utc>,
+    /// The upstream failed and this is an expired entry served instead.
+    pub stale: bool,
+}
+
+impl<T> Cached<T> {
+    pub fn age(&self) -> Duration {
+        (Utc::now() - self.fetched_at).to_std().unwrap_or_default()
+    }
+
+    /// Short human readable age for embed footers.
+    pub fn age_text(&self) -> String {
+        let secs = self.age().as_secs();
+        let age = match secs {
+            0..=4 => return "Live data".to_string(),
+            5..=119 => format!("{}s ago", secs),
+            _ => format!("{}m ago", secs / 60),
+        };
+        if self.stale {
+            format!("Upstream unavailable, showing data from {}", age)
+        } else {
+            format!("Data from {}", age)
+        }
+    }
+}
+
+/// In-memory cache keyed by normalised query. Entries are fresh for `ttl`
+/// and kept for `max_stale` so they can stand in when the upstream errors.
+pub struct TtlCache<T> {
+    ttl: Duration,
+    max_stale: Duration,
+    entries: Mutex<HashMap<String, (DateTime<Utc>, T)>>,
+}
+
+impl<T: Clone> TtlCache<T> {
+    pub fn new(ttl: Duration, max_stale: Duration) -> Self {
+        Self {
+            ttl,
+            max_stale,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lowercases and collapses whitespace so `PEPE ` and `pepe` share an entry.
+    pub fn normalise(query: &str) -> String {
+        query
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    fn lookup(&self, key: &str, max_age: Duration) -> Option<Cached<T>> {
+        let entries = self.entries.lock().expect("Cache lock poisoned");
+        let (fetched_at, value) = entries.get(key)?;
+        let age = (Utc::now() - *fetched_at).to_std().unwrap_or_default();
+        (age < max_age).then(|| Cached {
+            value: value.clone(),
+            fetched_at: *fetched_at,
+            stale: age >= self.ttl,
+        })
+    }
+
+    /// An entry younger than the TTL.
+    pub fn fresh(&self, key: &str) -> Option<Cached<T>> {
+        self.lookup(&Self::normalise(key), self.ttl)
+    }
+
+    /// Any entry still within `max_stale`, meant as a stand-in when the upstream errors.
+    pub fn stale(&self, key: &str) -> Option<Cached<T>> {
+        self.lookup(&Self::normalise(key), self.max_stale)
+    }
+
+    pub fn insert(&self, key: &str, value: T) -> Cached<T> {
+        let now = Utc::now();
+        let mut entries = self.entries.lock().expect("Cache lock poisoned");
+        entries.retain(|_, (fetched_at, _)| {
+            (now - *fetched_at).to_std().unwrap_or_default() < self.max_stale
+        });
+        if entries.len() >= MAX_ENTRIES {
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (fetched_at, _))| *fetched_at)
+                .map(|(key, _)| key.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(Self::normalise(key), (now, value.clone()));
+        Cached {
+            value,
+            fetched_at: now,
+            stale: false,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use image::{ImageOutputFormat, Rgb, RgbImage};
+use std::io::Cursor;
+
+const BACKGROUND: Rgb<u8> = Rgb([47, 49, 54]);
+const UP: Rgb<u8> = Rgb([67, 181, 129]);
+const DOWN: Rgb<u8> = Rgb([240, 71, 71]);
+const PADDING: u32 = 8;
+
+/// Renders a sparkline of `(unix_seconds, price)` points as a PNG.
+/// Returns `None` when there are fewer than two points to draw.
+pub fn sparkline(points: &[(i64, f64)], width: u32, height: u32) -> Option<Vec<u8>> {
+    if points.len() < 2 || width <= PADDING * 2 || height <= PADDING * 2 {
+        return None;
+    }
+    let (first_t, last_t) = (points[0].0, points[points.len() - 1].0);
+    let (min_p, max_p) = points
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(lo, hi), (_, p)| {
+            (lo.min(*p), hi.max(*p))
+        });
+    let span_t = (last_t - first_t).max(1) as f64;
+    let span_p = if max_p > min_p { max_p - min_p } else { 1.0 };
+    let inner_w = (width - PADDING * 2) as f64;
+    let inner_h = (height - PADDING * 2) as f64;
+
+    let to_pixel = |(t, p): (i64, f64)| {
+        let x = PADDING as f64 + (t - first_t) as f64 / span_t * inner_w;
+        let y = PADDING as f64 + (1.0 - (p - min_p) / span_p) * inner_h;
+        (x, y)
+    };
+    let colour = if points[points.len() - 1].1 >= points[0].1 {
+        UP
+    } else {
+        DOWN
+    };
+    let fill = Rgb([
+        blend(BACKGROUND[0], colour[0]),
+        blend(BACKGROUND[1], colour[1]),
+        blend(BACKGROUND[2], colour[2]),
+    ]);
+
+    let mut line = vec![];
+    for pair in points.windows(2) {
+        let (x0, y0) = to_pixel(pair[0]);
+        let (x1, y1) = to_pixel(pair[1]);
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as u32;
+        for step in 0..=steps {
+            let f = step as f64 / steps as f64;
+            line.push((
+                (x0 + (x1 - x0) * f).round() as u32,
+                (y0 + (y1 - y0) * f).round() as u32,
+            ));
+        }
+    }
+
+    let mut img = RgbImage::from_pixel(width, height, BACKGROUND);
+    // Shade the area under the line first so it never paints over the line itself
+    for &(x, y) in &line {
+        for fy in (y + 1)..(height - PADDING) {
+            img.put_pixel(x, fy, fill);
+        }
+    }
+    for &(x, y) in &line {
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            if x + dx < width && y + dy < height {
+                img.put_pixel(x + dx, y + dy, colour);
+            }
+        }
+    }
+
+    let mut bytes = Cursor::new(vec![]);
+    img.write_to(&mut bytes, ImageOutputFormat::Png).ok()?;
+    Some(bytes.into_inner())
+}
+
+fn blend(background: u8, colour: u8) -> u8 {
+    ((background as u16 * 3 + colour as u16) / 4) as u8
+}
@@ -0,0 +1,56 @@
+use serenity::model::id::{GuildId, UserId};
+use tracing::{info_span, warn, Span};
+use tracing_subscriber::EnvFilter;
+
+/// Used when `LOG_LEVEL` is not set. Serenity is chatty at info.
+pub const DEFAULT_LOG_LEVEL: &str = "info,serenity=warn";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    /// `json` for JSON lines, anything else for plain text.
+    pub fn parse(format: &str) -> Self {
+        if format.trim().eq_ignore_ascii_case("json") {
+            Self::Json
+        } else {
+            Self::Text
+        }
+    }
+}
+
+/// Filter directives like `info` or `warn,ronnbot_rs=debug`, the default when they do not parse.
+pub fn filter(level: Option<&str>) -> EnvFilter {
+    level
+        .and_then(|level| EnvFilter::try_new(level).ok())
+        .unwrap_or_else(|| EnvFilter::new(DEFAULT_LOG_LEVEL))
+}
+
+/// Installs the log subscriber for the standalone binary. Returns false when
+/// one is already installed, in which case its settings stay in place.
+pub fn init(level: Option<&str>, format: LogFormat) -> bool {
+    let builder = tracing_subscriber::fmt().with_env_filter(filter(level));
+    let installed = match format {
+        LogFormat::Text => builder.try_init().is_ok(),
+        LogFormat::Json => builder.json().try_init().is_ok(),
+    };
+    if !installed {
+        warn!("A log subscriber is already installed, LOG_LEVEL and LOG_FORMAT are ignored");
+    }
+    installed
+}
+
+/// Span every command runs or is logged in, prefix, custom and slash alike.
+pub fn command_span(kind: &str, name: &str, guild_id: Option<GuildId>, user_id: UserId) -> Span {
+    info_span!(
+        "command",
+        kind,
+        name,
+        guild = guild_id.map(|id| id.0),
+        user = user_id.0
+    )
+}
@@ -1 +1,8 @@
+pub mod apiclient;
+pub mod assets;
+pub mod cache;
+pub mod chart;
+pub mod logging;
+pub mod responder;
+pub mod store;
 pub mod utils;
@@ -0,0 +1,252 @@
+use serenity::builder::{
+    CreateAllowedMentions, CreateComponents, CreateEmbed, CreateInteractionResponseData,
+    CreateInteractionResponseFollowup, CreateMessage, ParseValue,
+};
+use serenity::http::Http;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::{AttachmentType, Message, ReactionType};
+use serenity::model::id::ChannelId;
+use serenity::model::Timestamp;
+use serenity::utils::Colour;
+
+/// What a command wants to say, independent of how it reaches Discord.
+/// Command bodies build one of these so they can be tested without a gateway.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CommandResponse {
+    pub content: Option<String>,
+    pub embed: Option<ResponseEmbed>,
+    pub attachments: Vec<ResponseAttachment>,
+    /// Added to the message after it is sent, prefix commands only.
+    pub reactions: Vec<ReactionType>,
+    /// Only visible to the caller, slash commands only.
+    pub ephemeral: bool,
+    /// Shown in one row below the message.
+    pub buttons: Vec<ResponseButton>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ResponseButton {
+    pub custom_id: String,
+    pub label: String,
+    pub disabled: bool,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ResponseEmbed {
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub fields: Vec<(String, String, bool)>,
+    pub colour: Option<Colour>,
+    pub footer: Option<String>,
+    pub image: Option<String>,
+    pub timestamp: Option<Timestamp>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ResponseAttachment {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+impl CommandResponse {
+    pub fn text(content: impl Into<String>) -> Self {
+        Self {
+            content: Some(content.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn embed(embed: ResponseEmbed) -> Self {
+        Self {
+            embed: Some(embed),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_attachment(mut self, filename: impl Into<String>, data: Vec<u8>) -> Self {
+        self.attachments.push(ResponseAttachment {
+            filename: filename.into(),
+            data,
+        });
+        self
+    }
+
+    pub fn with_reactions(mut self, reactions: impl IntoIterator<Item = ReactionType>) -> Self {
+        self.reactions.extend(reactions);
+        self
+    }
+
+    pub fn with_button(
+        mut self,
+        custom_id: impl Into<String>,
+        label: impl Into<String>,
+        disabled: bool,
+    ) -> Self {
+        self.buttons.push(ResponseButton {
+            custom_id: custom_id.into(),
+            label: label.into(),
+            disabled,
+        });
+        self
+    }
+
+    pub fn ephemeral(mut self) -> Self {
+        self.ephemeral = true;
+        self
+    }
+
+    fn files(&self) -> impl Iterator<Item = AttachmentType<'_>> {
+        self.attachments
+            .iter()
+            .map(|attachment| AttachmentType::Bytes {
+                data: attachment.data.as_slice().into(),
+                filename: attachment.filename.clone(),
+            })
+    }
+
+    fn components(&self) -> CreateComponents {
+        let mut components = CreateComponents::default();
+        if !self.buttons.is_empty() {
+            components.create_action_row(|row| {
+                for button in &self.buttons {
+                    row.create_button(|b| {
+                        b.custom_id(&button.custom_id)
+                            .label(&button.label)
+                            .style(ButtonStyle::Secondary)
+                            .disabled(button.disabled)
+                    });
+                }
+                row
+            });
+        }
+        components
+    }
+
+    pub fn apply_message<'a, 'b>(
+        &'a self,
+        m: &'b mut CreateMessage<'a>,
+    ) -> &'b mut CreateMessage<'a> {
+        if let Some(content) = &self.content {
+            m.content(content);
+        }
+        if let Some(embed) = &self.embed {
+            m.set_embed(embed.build());
+        }
+        m.set_components(self.components())
+            .allowed_mentions(users_only)
+            .add_files(self.files())
+    }
+
+    pub fn apply_followup<'a, 'b>(
+        &'a self,
+        f: &'b mut CreateInteractionResponseFollowup<'a>,
+    ) -> &'b mut CreateInteractionResponseFollowup<'a> {
+        if let Some(content) = &self.content {
+            f.content(content);
+        }
+        if let Some(embed) = &self.embed {
+            f.add_embed(embed.build());
+        }
+        f.set_components(self.components())
+            .allowed_mentions(users_only)
+            .ephemeral(self.ephemeral)
+            .add_files(self.files())
+    }
+
+    /// Same as `apply_followup`, for an interaction that was not deferred.
+    pub fn apply_response<'a, 'b>(
+        &'a self,
+        d: &'b mut CreateInteractionResponseData<'a>,
+    ) -> &'b mut CreateInteractionResponseData<'a> {
+        if let Some(content) = &self.content {
+            d.content(content);
+        }
+        if let Some(embed) = &self.embed {
+            d.add_embed(embed.build());
+        }
+        d.set_components(self.components())
+            .allowed_mentions(users_only)
+            .ephemeral(self.ephemeral)
+            .add_files(self.files())
+    }
+
+    /// Answers an interaction that was not deferred.
+    pub async fn respond(
+        &self,
+        http: &Http,
+        command: &ApplicationCommandInteraction,
+    ) -> serenity::Result<()> {
+        command
+            .create_interaction_response(http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| self.apply_response(d))
+            })
+            .await
+    }
+
+    /// Replaces the message a button was clicked on.
+    pub async fn update(
+        &self,
+        http: &Http,
+        component: &MessageComponentInteraction,
+    ) -> serenity::Result<()> {
+        component
+            .create_interaction_response(http, |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| self.apply_response(d))
+            })
+            .await
+    }
+
+    /// Sends the response as a regular message, then adds any reactions.
+    pub async fn send(&self, http: &Http, channel_id: ChannelId) -> serenity::Result<Message> {
+        let message = channel_id
+            .send_message(http, |m| self.apply_message(m))
+            .await?;
+        for reaction in &self.reactions {
+            message.react(http, reaction.clone()).await?;
+        }
+        Ok(message)
+    }
+}
+
+/// Replies may carry text members wrote, like tags, so only user mentions ping.
+/// `@everyone`, `@here` and role mentions are shown but notify nobody.
+fn users_only(mentions: &mut CreateAllowedMentions) -> &mut CreateAllowedMentions {
+    mentions.empty_parse().parse(ParseValue::Users)
+}
+
+impl ResponseEmbed {
+    pub fn build(&self) -> CreateEmbed {
+        let mut e = CreateEmbed::default();
+        if let Some(title) = &self.title {
+            e.title(title);
+        }
+        if let Some(url) = &self.url {
+            e.url(url);
+        }
+        if let Some(description) = &self.description {
+            e.description(description);
+        }
+        for (name, value, inline) in &self.fields {
+            e.field(name, value, *inline);
+        }
+        if let Some(colour) = self.colour {
+            e.colour(colour);
+        }
+        if let Some(footer) = &self.footer {
+            e.footer(|f| f.text(footer));
+        }
+        if let Some(image) = &self.image {
+            e.image(image);
+        }
+        if let Some(timestamp) = self.timestamp {
+            e.timestamp(timestamp);
+        }
+        e
+    }
+}
@@ -0,0 +1,156 @@
+use crate::data::storemap::StoreMap;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serenity::prelude::{TypeMap, TypeMapKey};
+use std::future::Future;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tracing::error;
+
+/// Tiny JSON key/value store on disk, one file per key under `root`.
+pub struct Store {
+    root: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl Store {
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self {
+            root,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(format!("{}.json", key))
+    }
+
+    /// Loads `key`, `None` when it was never saved. A file that cannot be read or parsed
+    /// is an error and is left untouched, so it is never saved over with a default.
+    pub async fn load<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let path = self.path(key);
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(why) if why.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(why) => {
+                return Err(why).with_context(|| format!("Could not read {}", path.display()))
+            }
+        };
+        serde_json::from_slice(&bytes).map(Some).with_context(|| {
+            format!(
+                "Store entry '{}' is corrupt, fix or move {} away",
+                key,
+                path.display()
+            )
+        })
+    }
+
+    pub async fn save<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+        // Write to a temp file first so a crash mid-write never leaves a truncated entry
+        let tmp = self.root.join(format!("{}.json.tmp", key));
+        tokio::fs::write(&tmp, serde_json::to_vec_pretty(value)?).await?;
+        tokio::fs::rename(&tmp, self.path(key)).await?;
+        Ok(())
+    }
+
+    fn file_path(&self, name: &str) -> PathBuf {
+        self.root.join("files").join(name)
+    }
+
+    /// Raw bytes kept next to the JSON entries, like images uploaded for tags.
+    pub async fn save_file(&self, name: &str, data: &[u8]) -> Result<()> {
+        tokio::fs::create_dir_all(self.root.join("files")).await?;
+        tokio::fs::write(self.file_path(name), data).await?;
+        Ok(())
+    }
+
+    pub async fn load_file(&self, name: &str) -> std::io::Result<Vec<u8>> {
+        tokio::fs::read(self.file_path(name)).await
+    }
+
+    pub async fn remove_file(&self, name: &str) -> std::io::Result<()> {
+        tokio::fs::remove_file(self.file_path(name)).await
+    }
+}
+
+/// Saves the value behind the TypeMap key `K` as `key`. The value is copied under the read
+/// lock so nothing waits on the disk.
+pub async fn persist<K, V>(data: &RwLock<TypeMap>, key: &str) -> Result<()>
+where
+    K: TypeMapKey<Value = Arc<RwLock<V>>>,
+    V: Serialize + Clone + Send + Sync,
+{
+    let (value, store) = {
+        let data_read = data.read().await;
+        (
+            data_read
+                .get::<K>()
+                .expect("Expected the persisted key in TypeMap.")
+                .clone(),
+            data_read
+                .get::<StoreMap>()
+                .expect("Expected StoreMap in TypeMap.")
+                .clone(),
+        )
+    };
+    let snapshot = value.read().await.clone();
+    store.save(key, &snapshot).await
+}
+
+/// Batches saves of state that changes often: [`Dirty::mark`] after a change and the loop
+/// from [`Dirty::start`] saves once per period at most.
+pub struct Dirty {
+    started: AtomicBool,
+    dirty: AtomicBool,
+}
+
+impl Dirty {
+    pub const fn new() -> Self {
+        Self {
+            started: AtomicBool::new(false),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    pub fn mark(&self) {
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    /// Spawns the loop that runs `save` every `period` when marked, marking again when
+    /// it fails. Only the first call starts it.
+    pub fn start<F, Fut>(&'static self, what: &'static str, period: Duration, save: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send,
+    {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if self.dirty.swap(false, Ordering::SeqCst) {
+                    if let Err(why) = save().await {
+                        self.mark();
+                        error!("{} could not be saved: {}", what, why);
+                    }
+                }
+            }
+        });
+    }
+}
+
+impl Default for Dirty {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,3 +1,6 @@
+use serenity::model::misc::EmojiIdentifier;
+use serenity::utils::parse_emoji;
+
 pub fn prettify_int(int: f64) -> String {
     let mut s = String::new();
     let int_str = int.to_string();
@@ -10,3 +13,26 @@ pub fn prettify_int(int: f64) -> String {
     }
     s
 }
+
+/// Every custom emoji anywhere in `text`, once each, in order of appearance.
+pub fn custom_emojis(text: &str) -> Vec<EmojiIdentifier> {
+    let mut emojis: Vec<EmojiIdentifier> = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        match parse_emoji(&rest[..=end]) {
+            Some(emoji) => {
+                if !emojis.iter().any(|seen| seen.id == emoji.id) {
+                    emojis.push(emoji);
+                }
+                rest = &rest[end + 1..];
+            }
+            // Could be `<` followed by an emoji, retry from the next character
+            None => rest = &rest[1..],
+        }
+    }
+    emojis
+}
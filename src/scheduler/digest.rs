@@ -0,0 +1,101 @@
+use super::DigestSchedule;
+use crate::providers::MarketData;
+use crate::ronn_utils::utils::prettify_int;
+use crate::slashcommands::{coin, floorprice};
+use serenity::http::Http;
+use serenity::model::prelude::*;
+use serenity::utils::Colour;
+
+/// One embed field per tracked token or collection: (name, value).
+pub async fn fields(
+    market: &MarketData,
+    guild_id: GuildId,
+    schedule: &DigestSchedule,
+) -> Vec<(String, String)> {
+    let mut fields = vec![];
+    for query in &schedule.tokens {
+        fields.push(token_field(market, guild_id, query).await);
+    }
+    for query in &schedule.collections {
+        fields.push(collection_field(market, guild_id, query).await);
+    }
+    fields
+}
+
+async fn token_field(market: &MarketData, guild_id: GuildId, query: &str) -> (String, String) {
+    match coin::search(market, Some(guild_id), query).await {
+        Ok(result) if !result.value.pairs.is_empty() => {
+            let pair = &result.value.pairs[0];
+            (
+                format!("{} ({})", pair.base_token.name, pair.base_token.symbol),
+                format!(
+                    "Price: ${}\n24h: {}%\nVol: ${}",
+                    pair.price_usd.clone().unwrap_or_else(|| "?".to_string()),
+                    pair.price_change.h24,
+                    prettify_int(pair.volume.h24)
+                ),
+            )
+        }
+        Ok(_) => (query.to_string(), "No pair found".to_string()),
+        Err(why) => (query.to_string(), format!("Unavailable: {}", why)),
+    }
+}
+
+async fn collection_field(market: &MarketData, guild_id: GuildId, query: &str) -> (String, String) {
+    match floorprice::call_api(market, Some(guild_id), query).await {
+        Ok(result) if !result.value.collections.is_empty() => {
+            let collection = &result.value.collections[0];
+            let floor = match &collection.floor_ask.price {
+                Some(p) => p.amount.decimal,
+                None => 0.0,
+            };
+            let change = collection
+                .floor_sale_change
+                .as_ref()
+                .and_then(|change| change.one_day)
+                .map_or("?".to_string(), |ratio| {
+                    format!("{:.2}", (ratio - 1.0) * 100.0)
+                });
+            let volume = collection
+                .volume
+                .as_ref()
+                .and_then(|volume| volume.one_day)
+                .unwrap_or_default();
+            (
+                collection.name.clone(),
+                format!(
+                    "Floor: {}ETH on {}\n24h: {}%\nVol: {:.2}ETH",
+                    floor, collection.floor_ask.source_domain, change, volume
+                ),
+            )
+        }
+        Ok(_) => (query.to_string(), "No collection found".to_string()),
+        Err(why) => (query.to_string(), format!("Unavailable: {}", why)),
+    }
+}
+
+pub async fn post(
+    http: &Http,
+    market: &MarketData,
+    guild_id: GuildId,
+    schedule: &DigestSchedule,
+) -> Result<(), anyhow::Error> {
+    let fields = fields(market, guild_id, schedule).await;
+    ChannelId(schedule.channel_id)
+        .send_message(http, |m| {
+            m.embed(|e| {
+                e.title("Market Digest")
+                    .colour(Colour::BLURPLE)
+                    .timestamp(Timestamp::now());
+                if fields.is_empty() {
+                    e.description("Nothing tracked yet, add some with /digest track");
+                }
+                for (name, value) in fields {
+                    e.field(name, value, true);
+                }
+                e
+            })
+        })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::data::{digestmap::DigestMap, marketdatamap::MarketDataMap};
+use crate::ronn_utils::store::persist;
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use serenity::http::Http;
+use serenity::model::id::GuildId;
+use serenity::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, warn};
+
+pub mod digest;
+
+pub const STORE_KEY: &str = "digests";
+pub const MAX_TRACKED: usize = 10;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestSchedule {
+    pub channel_id: u64,
+    pub cron: String,
+    pub tokens: Vec<String>,
+    pub collections: Vec<String>,
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+/// Digest schedules keyed by guild id.
+pub type Schedules = HashMap<u64, DigestSchedule>;
+
+impl DigestSchedule {
+    /// True when the schedule fired at least once since the last post.
+    /// A run missed while the bot was offline is posted once on the next tick.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        let (Ok(schedule), Some(last_run)) = (parse_schedule(&self.cron), self.last_run) else {
+            return false;
+        };
+        schedule
+            .after(&last_run)
+            .next()
+            .is_some_and(|next| next <= now)
+    }
+
+    pub fn next_run(&self) -> Option<DateTime<Utc>> {
+        parse_schedule(&self.cron).ok()?.upcoming(Utc).next()
+    }
+}
+
+/// Weekday names of standard cron, numbered from Sunday as 0.
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A standard cron weekday, a name or 0-7 where both 0 and 7 are Sunday.
+fn weekday(value: &str) -> Option<u32> {
+    let value = value.to_lowercase();
+    WEEKDAYS
+        .iter()
+        .position(|day| *day == value)
+        .map(|day| day as u32)
+        .or_else(|| value.parse().ok().filter(|day| *day <= 7))
+}
+
+/// Rewrites a standard day-of-week field for the `cron` crate, which numbers the days
+/// 1-7 from Sunday. Lists, ranges and steps are expanded to the days they name.
+fn translate_weekdays(field: &str) -> Option<String> {
+    if field == "*" || field == "?" {
+        return Some(field.to_string());
+    }
+    let mut days = BTreeSet::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse().ok().filter(|step| *step > 0)?),
+            None => (part, 1),
+        };
+        let (start, end) = match (range, range.split_once('-')) {
+            ("*", _) => (0, 6),
+            (_, Some((start, end))) => (weekday(start)?, weekday(end)?),
+            // `5/2` runs from Friday to the end of the week
+            (_, None) if step > 1 => (weekday(range)?, 6),
+            (_, None) => (weekday(range)?, weekday(range)?),
+        };
+        if start > end {
+            return None;
+        }
+        days.extend((start..=end).step_by(step).map(|day| day % 7 + 1));
+    }
+    Some(
+        days.iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+/// Accepts `daily`, `hourly`, standard 5-field cron, or the 6/7-field form with seconds.
+/// Weekdays in 5-field cron are 0-7 from Sunday as usual. The 6/7-field form goes to the
+/// `cron` crate unchanged, where they are 1-7 from Sunday.
+pub fn parse_schedule(expr: &str) -> Result<Schedule, anyhow::Error> {
+    let expr = expr.trim();
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    let normalised = match expr.to_lowercase().as_str() {
+        "daily" => "@daily".to_string(),
+        "hourly" => "@hourly".to_string(),
+        _ if fields.len() == 5 => {
+            let weekdays = translate_weekdays(fields[4])
+                .ok_or_else(|| anyhow!("Invalid schedule `{}`: bad day of week", expr))?;
+            format!("0 {} {}", fields[..4].join(" "), weekdays)
+        }
+        _ => expr.to_string(),
+    };
+    Schedule::from_str(&normalised).map_err(|why| anyhow!("Invalid schedule `{}`: {}", expr, why))
+}
+
+/// Spawns the digest loop. Safe to call on every `ready`, only the first call starts it.
+pub fn start(http: Arc<Http>, data: Arc<RwLock<TypeMap>>) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(30));
+        loop {
+            interval.tick().await;
+            run_due(&http, &data).await;
+        }
+    });
+}
+
+async fn run_due(http: &Http, data: &Arc<RwLock<TypeMap>>) {
+    let (schedules, market) = {
+        let data_read = data.read().await;
+        (
+            data_read
+                .get::<DigestMap>()
+                .expect("Expected DigestMap in TypeMap.")
+                .clone(),
+            data_read
+                .get::<MarketDataMap>()
+                .expect("Expected MarketDataMap in TypeMap.")
+                .clone(),
+        )
+    };
+    let now = Utc::now();
+    let due: Vec<(u64, DigestSchedule)> = schedules
+        .read()
+        .await
+        .iter()
+        .filter(|(_, schedule)| schedule.is_due(now))
+        .map(|(guild_id, schedule)| (*guild_id, schedule.clone()))
+        .collect();
+    if due.is_empty() {
+        return;
+    }
+
+    for (guild_id, schedule) in due {
+        if let Err(why) = digest::post(http, &market, GuildId(guild_id), &schedule).await {
+            warn!("Digest for guild {} could not be posted: {}", guild_id, why);
+        }
+        // Mark the run even on failure so a dead channel doesn't retry every tick
+        if let Some(schedule) = schedules.write().await.get_mut(&guild_id) {
+            schedule.last_run = Some(now);
+        }
+    }
+    if let Err(why) = persist::<DigestMap, _>(data, STORE_KEY).await {
+        error!("Digest schedules could not be saved: {}", why);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::data::settingsmap::SettingsMap;
+use serde::{Deserialize, Serialize};
+use serenity::model::channel::Message;
+use serenity::prelude::Context;
+use std::collections::HashMap;
+
+pub const STORE_KEY: &str = "settings";
+pub const DEFAULT_PREFIX: &str = "!";
+pub const MAX_PREFIXES: usize = 5;
+pub const MAX_PREFIX_LEN: usize = 5;
+
+/// Per guild bot settings changed through /settings.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// Empty means the default prefix.
+    pub prefixes: Vec<String>,
+}
+
+/// Guild id to its settings.
+pub type Settings = HashMap<u64, GuildSettings>;
+
+/// The prefixes a guild answers to, the default when none are set.
+pub fn prefixes(settings: Option<&GuildSettings>) -> Vec<String> {
+    match settings {
+        Some(settings) if !settings.prefixes.is_empty() => settings.prefixes.clone(),
+        _ => vec![DEFAULT_PREFIX.to_string()],
+    }
+}
+
+/// The longest of `prefixes` that `content` starts with.
+pub fn matching_prefix(prefixes: &[String], content: &str) -> Option<String> {
+    prefixes
+        .iter()
+        .filter(|prefix| content.starts_with(prefix.as_str()))
+        .max_by_key(|prefix| prefix.len())
+        .cloned()
+}
+
+/// Reads space separated prefixes, dropping duplicates.
+pub fn parse_prefixes(input: &str) -> Result<Vec<String>, String> {
+    let mut prefixes: Vec<String> = vec![];
+    for prefix in input.split_whitespace() {
+        if prefix.chars().count() > MAX_PREFIX_LEN || prefix.starts_with('/') {
+            return Err(format!(
+                "`{}` is not a usable prefix, prefixes are up to {} characters and cannot start with /",
+                prefix, MAX_PREFIX_LEN
+            ));
+        }
+        if !prefixes.iter().any(|p| p == prefix) {
+            prefixes.push(prefix.to_string());
+        }
+    }
+    if prefixes.is_empty() {
+        return Err("Please give at least one prefix".to_string());
+    }
+    if prefixes.len() > MAX_PREFIXES {
+        return Err(format!(
+            "A server can have at most {} prefixes",
+            MAX_PREFIXES
+        ));
+    }
+    Ok(prefixes)
+}
+
+/// The prefix `msg` was sent with, for the framework's dynamic prefix hook.
+pub async fn message_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+    let settings = ctx
+        .data
+        .read()
+        .await
+        .get::<SettingsMap>()
+        .expect("Expected SettingsMap in TypeMap.")
+        .clone();
+    let settings = settings.read().await;
+    let guild = msg.guild_id.and_then(|id| settings.get(&id.0));
+    matching_prefix(&prefixes(guild), &msg.content)
+}
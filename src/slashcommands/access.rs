@@ -0,0 +1,393 @@
+use super::{registry, string_option};
+use crate::access::{self, default_rule, rule_for, Caller, Denied, GuildAccess, SETTINGS_COMMAND};
+use crate::bot::is_reserved;
+use crate::customcommands::{self, CustomCommands};
+use crate::data::{accessmap::AccessMap, customcommandmap::CustomCommandMap};
+use crate::ronn_utils::store::persist;
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommandOption};
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::{
+    ApplicationCommandInteraction, CommandDataOptionValue,
+};
+use serenity::model::Permissions;
+use serenity::prelude::*;
+use tracing::error;
+
+/// Permissions that can be required through /access, by choice value.
+pub const PERMISSION_CHOICES: &[(&str, Permissions)] = &[
+    ("none", Permissions::empty()),
+    ("manage_messages", Permissions::MANAGE_MESSAGES),
+    ("manage_roles", Permissions::MANAGE_ROLES),
+    ("manage_emojis", Permissions::MANAGE_EMOJIS_AND_STICKERS),
+    ("manage_guild", Permissions::MANAGE_GUILD),
+    ("administrator", Permissions::ADMINISTRATOR),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelMode {
+    Allow,
+    Deny,
+    Clear,
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name(SETTINGS_COMMAND)
+        .description("Control who can use which bot commands and where")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("disable")
+                .description("Turn a command off in this server")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| command_option(sub).required(true))
+        })
+        .create_option(|option| {
+            option
+                .name("enable")
+                .description("Turn a command back on")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| command_option(sub).required(true))
+        })
+        .create_option(|option| {
+            option
+                .name("role")
+                .description("Add or remove a role that may use a command")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| command_option(sub).required(true))
+                .create_sub_option(|sub| {
+                    sub.name("role")
+                        .description("Members need one of the listed roles")
+                        .kind(CommandOptionType::Role)
+                        .required(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("permission")
+                .description("Require a permission for a command")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| command_option(sub).required(true))
+                .create_sub_option(|sub| {
+                    sub.name("permission")
+                        .description("Permission members need")
+                        .kind(CommandOptionType::String)
+                        .required(true);
+                    for (name, _) in PERMISSION_CHOICES {
+                        sub.add_string_choice(name, name);
+                    }
+                    sub
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("channel")
+                .description("Allow or deny commands in a channel")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("The channel")
+                        .kind(CommandOptionType::Channel)
+                        .required(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("mode")
+                        .description("allow limits commands to listed channels, deny blocks it")
+                        .kind(CommandOptionType::String)
+                        .add_string_choice("allow", "allow")
+                        .add_string_choice("deny", "deny")
+                        .add_string_choice("clear", "clear")
+                        .required(true)
+                })
+                .create_sub_option(|sub| command_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("show")
+                .description("Show the rules for this server or one command")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| command_option(sub))
+        })
+}
+
+fn command_option(sub: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    sub.name("command")
+        .description("Command name without ! or /, leave out for every command")
+        .kind(CommandOptionType::String)
+}
+
+/// Whether `name` is a prefix, slash or custom command of the guild, rules for anything
+/// else would never apply.
+pub fn known_command(commands: &CustomCommands, guild_id: u64, name: &str) -> bool {
+    is_reserved(name)
+        || registry()
+            .iter()
+            .any(|command| command.0.get("name").and_then(|value| value.as_str()) == Some(name))
+        || commands.contains_key(&customcommands::key(None, name))
+        || commands.contains_key(&customcommands::key(Some(guild_id), name))
+}
+
+pub fn set_disabled(access: &mut GuildAccess, command: &str, disabled: bool) -> String {
+    if command == SETTINGS_COMMAND {
+        return format!("`{}` cannot be turned off", SETTINGS_COMMAND);
+    }
+    let rule = access
+        .commands
+        .entry(command.to_string())
+        .or_insert_with(|| default_rule(command));
+    rule.disabled = disabled;
+    if disabled {
+        format!("`{}` is now disabled", command)
+    } else {
+        format!("`{}` is now enabled", command)
+    }
+}
+
+pub fn toggle_role(access: &mut GuildAccess, command: &str, role: u64) -> String {
+    let rule = access
+        .commands
+        .entry(command.to_string())
+        .or_insert_with(|| default_rule(command));
+    match rule.roles.iter().position(|r| *r == role) {
+        Some(index) => {
+            rule.roles.remove(index);
+            format!("<@&{}> is no longer required for `{}`", role, command)
+        }
+        None => {
+            rule.roles.push(role);
+            format!(
+                "`{}` now needs one of {} role(s)",
+                command,
+                rule.roles.len()
+            )
+        }
+    }
+}
+
+pub fn set_permission(access: &mut GuildAccess, command: &str, permissions: Permissions) -> String {
+    let rule = access
+        .commands
+        .entry(command.to_string())
+        .or_insert_with(|| default_rule(command));
+    rule.permissions = permissions.bits();
+    if permissions.is_empty() {
+        format!("`{}` no longer needs a permission", command)
+    } else {
+        format!("`{}` now needs the {} permission", command, permissions)
+    }
+}
+
+/// Without a command the channel rule applies to every command.
+pub fn set_channel(
+    access: &mut GuildAccess,
+    command: Option<&str>,
+    channel: u64,
+    mode: ChannelMode,
+) -> String {
+    let (allowed, denied) = match command {
+        Some(command) => {
+            let rule = access
+                .commands
+                .entry(command.to_string())
+                .or_insert_with(|| default_rule(command));
+            (&mut rule.allowed_channels, &mut rule.denied_channels)
+        }
+        None => (&mut access.allowed_channels, &mut access.denied_channels),
+    };
+    allowed.retain(|c| *c != channel);
+    denied.retain(|c| *c != channel);
+    let target = match command {
+        Some(command) => format!("`{}`", command),
+        None => "commands".to_string(),
+    };
+    match mode {
+        ChannelMode::Allow => {
+            allowed.push(channel);
+            format!("{} are allowed in <#{}>", target, channel)
+        }
+        ChannelMode::Deny => {
+            denied.push(channel);
+            format!("{} are blocked in <#{}>", target, channel)
+        }
+        ChannelMode::Clear => format!("<#{}> has no rule for {} anymore", channel, target),
+    }
+}
+
+fn channels(channels: &[u64]) -> String {
+    if channels.is_empty() {
+        "any".to_string()
+    } else {
+        channels
+            .iter()
+            .map(|c| format!("<#{}>", c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+pub fn describe(access: &GuildAccess, command: Option<&str>) -> String {
+    match command {
+        Some(command) => {
+            let rule = rule_for(Some(access), command);
+            let roles = if rule.roles.is_empty() {
+                "any".to_string()
+            } else {
+                rule.roles
+                    .iter()
+                    .map(|r| format!("<@&{}>", r))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let permissions = Permissions::from_bits_truncate(rule.permissions);
+            format!(
+                "`{}` is {}\nRoles: {}\nPermission: {}\nAllowed channels: {}\nBlocked channels: {}",
+                command,
+                if rule.disabled { "disabled" } else { "enabled" },
+                roles,
+                if permissions.is_empty() {
+                    "none".to_string()
+                } else {
+                    permissions.to_string()
+                },
+                channels(&rule.allowed_channels),
+                channels(&rule.denied_channels)
+            )
+        }
+        None => {
+            let mut disabled: Vec<&str> = access
+                .commands
+                .iter()
+                .filter(|(_, rule)| rule.disabled)
+                .map(|(name, _)| name.as_str())
+                .collect();
+            disabled.sort_unstable();
+            let mut customised: Vec<&str> = access.commands.keys().map(String::as_str).collect();
+            customised.sort_unstable();
+            let list = |names: &[&str]| {
+                if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names
+                        .iter()
+                        .map(|n| format!("`{}`", n))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+            };
+            format!(
+                "Allowed channels: {}\nBlocked channels: {}\nDisabled: {}\nCommands with rules: {}",
+                channels(&access.allowed_channels),
+                channels(&access.denied_channels),
+                list(&disabled),
+                list(&customised)
+            )
+        }
+    }
+}
+
+/// Checks a slash command against the guild's rules before it runs.
+pub async fn check_interaction(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> Result<(), Denied> {
+    let Some(guild_id) = command.guild_id else {
+        return Ok(());
+    };
+    let rules = ctx
+        .data
+        .read()
+        .await
+        .get::<AccessMap>()
+        .expect("Expected AccessMap in TypeMap.")
+        .clone();
+    let rules = rules.read().await;
+    let caller = command.member.as_ref().map(Caller::from_member);
+    access::check(
+        rules.get(&guild_id.0),
+        &command.data.name,
+        command.channel_id.0,
+        caller.as_ref(),
+    )
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) -> String {
+    let Some(guild_id) = command.guild_id else {
+        return "Access rules only exist inside a server".to_string();
+    };
+    let Some(subcommand) = command.data.options.first() else {
+        return "Please pick an access subcommand".to_string();
+    };
+    let rules = ctx
+        .data
+        .read()
+        .await
+        .get::<AccessMap>()
+        .expect("Expected AccessMap in TypeMap.")
+        .clone();
+    let options = &subcommand.options;
+    let target = string_option(options, "command")
+        .map(|command| command.trim_start_matches(['!', '/']).to_lowercase());
+    if let Some(command) = target.as_deref().filter(|_| subcommand.name != "show") {
+        let commands = ctx
+            .data
+            .read()
+            .await
+            .get::<CustomCommandMap>()
+            .expect("Expected CustomCommandMap in TypeMap.")
+            .clone();
+        if !known_command(&*commands.read().await, guild_id.0, command) {
+            return format!("`{}` is not a command of this bot", command);
+        }
+    }
+
+    let reply = {
+        let mut rules = rules.write().await;
+        let access = rules.entry(guild_id.0).or_default();
+        match (subcommand.name.as_str(), target.as_deref()) {
+            ("show", command) => return describe(access, command),
+            ("disable", Some(command)) => set_disabled(access, command, true),
+            ("enable", Some(command)) => set_disabled(access, command, false),
+            ("role", Some(command)) => {
+                let role = options.iter().find_map(|option| match &option.resolved {
+                    Some(CommandDataOptionValue::Role(role)) => Some(role.id.0),
+                    _ => None,
+                });
+                match role {
+                    Some(role) => toggle_role(access, command, role),
+                    None => return "Please pick a role".to_string(),
+                }
+            }
+            ("permission", Some(command)) => {
+                let choice = string_option(options, "permission").unwrap_or_default();
+                match PERMISSION_CHOICES.iter().find(|(name, _)| *name == choice) {
+                    Some((_, permissions)) => set_permission(access, command, *permissions),
+                    None => return format!("`{}` is not a known permission", choice),
+                }
+            }
+            ("channel", command) => {
+                let channel = options.iter().find_map(|option| match &option.resolved {
+                    Some(CommandDataOptionValue::Channel(channel)) => Some(channel.id.0),
+                    _ => None,
+                });
+                let mode = match string_option(options, "mode").as_deref() {
+                    Some("allow") => ChannelMode::Allow,
+                    Some("deny") => ChannelMode::Deny,
+                    _ => ChannelMode::Clear,
+                };
+                match channel {
+                    Some(channel) => set_channel(access, command, channel, mode),
+                    None => return "Please pick a channel".to_string(),
+                }
+            }
+            (_, None) => return "Please name a command".to_string(),
+            _ => return "Unknown access subcommand".to_string(),
+        }
+    };
+
+    if let Err(why) = persist::<AccessMap, _>(&ctx.data, access::STORE_KEY).await {
+        error!("Access rules could not be saved: {}", why);
+        return format!("{}\n(warning: rules could not be saved)", reply);
+    }
+    reply
+}
@@ -1,9 +1,23 @@
+use super::string_option;
+use crate::data::pricehistorymap::PriceHistoryMap;
+use crate::providers::MarketData;
+use crate::ronn_utils::apiclient::ApiError;
+use crate::ronn_utils::cache::Cached;
+use crate::ronn_utils::chart;
+use crate::ronn_utils::responder::{CommandResponse, ResponseEmbed};
+use crate::ronn_utils::store::{persist, Dirty};
+use crate::ronn_utils::utils::prettify_int;
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOptionValue;
 use serenity::model::prelude::command::CommandOptionType;
 use serenity::model::prelude::interaction::application_command::CommandDataOption;
+use serenity::model::prelude::GuildId;
+use serenity::prelude::*;
+use serenity::utils::Colour;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Root {
@@ -65,6 +79,12 @@ pub struct Volume {
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PriceChange {
     pub h24: f64,
+    #[serde(default)]
+    pub h6: f64,
+    #[serde(default)]
+    pub h1: f64,
+    #[serde(default)]
+    pub m5: f64,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -87,27 +107,202 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
         })
 }
 
-pub async fn run(options: &[CommandDataOption]) -> Result<Root, anyhow::Error> {
-    let option = options
-        .get(0)
-        .expect("Expected Query")
-        .resolved
-        .as_ref()
-        .expect("Query");
-    if let CommandDataOptionValue::String(coin) = option {
-        let apiresult = reqwest::get(format!(
-            "https://api.dexscreener.com/latest/dex/search?q={}",
-            coin
-        ))
-        .await
-        .map_err(|_| anyhow!("Dexscreener API cannot be reached"))?
-        .error_for_status()
-        .map_err(|_| anyhow!("No pair exists"))?
-        .json::<Root>()
-        .await
-        .map_err(|_| anyhow!("Error parsing Json"));
-        apiresult
-    } else {
-        Err(anyhow!("Please Provide a coin"))
+pub async fn run(
+    market: &MarketData,
+    guild_id: Option<GuildId>,
+    options: &[CommandDataOption],
+) -> Result<Cached<Root>, anyhow::Error> {
+    let coin =
+        string_option(options, "coinname").ok_or_else(|| anyhow!("Please Provide a coin"))?;
+    let result = search(market, guild_id, &coin).await?;
+    if result.value.pairs.is_empty() {
+        return Err(anyhow!("No pair exists"));
+    }
+    Ok(result)
+}
+
+pub async fn search(
+    market: &MarketData,
+    guild_id: Option<GuildId>,
+    coin: &str,
+) -> Result<Cached<Root>, ApiError> {
+    market.search_tokens(guild_id, coin).await
+}
+
+/// What `/coin` says when the lookup failed.
+pub fn error_response(why: &anyhow::Error) -> CommandResponse {
+    CommandResponse::text(format!(
+        "Some Error occured Contact Izeeyahmon.eth: {}",
+        why
+    ))
+}
+
+/// The `/coin` embed for the best pair, with the sparkline attached when there is one.
+pub fn response(result: &Cached<Root>, chart: Option<Vec<u8>>) -> CommandResponse {
+    let Some(pair) = result.value.pairs.first() else {
+        return CommandResponse::text("No pair exists");
+    };
+    let embed = ResponseEmbed {
+        title: Some(pair.base_token.name.clone()),
+        url: Some(pair.url.clone()),
+        fields: vec![
+            (
+                "Price".to_string(),
+                format!(
+                    "${} : {}%",
+                    pair.price_usd
+                        .clone()
+                        .unwrap_or("No value you got rugged bruh".to_string()),
+                    pair.price_change.h24
+                ),
+                true,
+            ),
+            (
+                "Liquidity".to_string(),
+                format!(
+                    "${}",
+                    prettify_int(pair.liquidity.clone().unwrap_or_default().usd)
+                ),
+                false,
+            ),
+            (
+                "Chain".to_string(),
+                format!("${}@{}", pair.chain_id, pair.dex_id),
+                false,
+            ),
+            (
+                "VOL".to_string(),
+                format!("${}", prettify_int(pair.volume.h24)),
+                false,
+            ),
+        ],
+        colour: Some(if pair.price_change.h24 > 0.0 {
+            Colour::DARK_GREEN
+        } else {
+            Colour::DARK_RED
+        }),
+        footer: Some(result.age_text()),
+        image: chart.as_ref().map(|_| "attachment://chart.png".to_string()),
+        ..Default::default()
+    };
+    let response = CommandResponse::embed(embed);
+    match chart {
+        Some(png) => response.with_attachment("chart.png", png),
+        None => response,
     }
 }
+
+pub const HISTORY_STORE_KEY: &str = "price_history";
+const HISTORY_WINDOW: i64 = 24 * 60 * 60;
+pub const HISTORY_SAMPLES: usize = 288;
+/// Spacing between kept samples, so the capped samples still span the window.
+const HISTORY_INTERVAL: i64 = HISTORY_WINDOW / HISTORY_SAMPLES as i64;
+/// Pairs tracked at once, the least recently charted go first.
+pub const HISTORY_PAIRS: usize = 500;
+
+/// `(unix_seconds, price_usd)` samples keyed by DexScreener pair address.
+pub type PriceHistory = HashMap<String, VecDeque<(i64, f64)>>;
+
+/// Price history changed since the last save.
+static HISTORY_CHANGES: Dirty = Dirty::new();
+
+/// Builds a 24h price series for `pair` from the provider's price change
+/// anchors (24h, 6h, 1h, 5m) merged with locally recorded samples.
+pub fn price_series(pair: &Pair, history: &VecDeque<(i64, f64)>, now: i64) -> Vec<(i64, f64)> {
+    let Some(price) = pair.price_usd.as_ref().and_then(|p| p.parse::<f64>().ok()) else {
+        return vec![];
+    };
+    let change = &pair.price_change;
+    let mut series: Vec<(i64, f64)> = [
+        (86400, change.h24),
+        (21600, change.h6),
+        (3600, change.h1),
+        (300, change.m5),
+    ]
+    .iter()
+    .filter(|(_, pct)| *pct > -100.0)
+    .map(|(ago, pct)| (now - ago, price / (1.0 + pct / 100.0)))
+    .chain(
+        history
+            .iter()
+            .copied()
+            .filter(|(t, _)| now - t < HISTORY_WINDOW && *t < now),
+    )
+    .collect();
+    series.push((now, price));
+    series.sort_by_key(|(t, _)| *t);
+    series.dedup_by_key(|(t, _)| *t);
+    series
+}
+
+/// Adds a sample for `pair_address` unless the last one is recent, then drops samples
+/// past the window, pairs nobody charted within it, and the oldest pairs over the cap.
+/// Returns whether anything changed.
+pub fn record_price(history: &mut PriceHistory, pair_address: &str, price: f64, now: i64) -> bool {
+    let samples = history.entry(pair_address.to_string()).or_default();
+    let added = samples
+        .back()
+        .is_none_or(|(t, _)| now - t >= HISTORY_INTERVAL);
+    if added {
+        samples.push_back((now, price));
+    }
+    let total = |history: &PriceHistory| history.values().map(VecDeque::len).sum::<usize>();
+    let before = total(history);
+    for samples in history.values_mut() {
+        while samples.len() > HISTORY_SAMPLES
+            || samples
+                .front()
+                .is_some_and(|(t, _)| now - t > HISTORY_WINDOW)
+        {
+            samples.pop_front();
+        }
+    }
+    history.retain(|_, samples| !samples.is_empty());
+    if history.len() > HISTORY_PAIRS {
+        let mut last_used: Vec<(i64, String)> = history
+            .iter()
+            .map(|(address, samples)| (samples.back().map_or(0, |(t, _)| *t), address.clone()))
+            .collect();
+        last_used.sort();
+        for (_, address) in last_used.iter().take(history.len() - HISTORY_PAIRS) {
+            history.remove(address);
+        }
+    }
+    added || total(history) != before
+}
+
+/// Records the current price of `pair` and renders its 24h sparkline as a PNG.
+/// The history is saved by the loop from [`start`].
+pub async fn chart(data: &Arc<RwLock<TypeMap>>, pair: &Pair) -> Option<Vec<u8>> {
+    let history = data
+        .read()
+        .await
+        .get::<PriceHistoryMap>()
+        .expect("Expected PriceHistoryMap in TypeMap.")
+        .clone();
+    let now = chrono::Utc::now().timestamp();
+    let series = {
+        let mut history = history.write().await;
+        let series = price_series(
+            pair,
+            history.get(&pair.pair_address).unwrap_or(&VecDeque::new()),
+            now,
+        );
+        if let Some(price) = pair.price_usd.as_ref().and_then(|p| p.parse::<f64>().ok()) {
+            if record_price(&mut history, &pair.pair_address, price, now) {
+                HISTORY_CHANGES.mark();
+            }
+        }
+        series
+    };
+    chart::sparkline(&series, 600, 200)
+}
+
+/// Spawns the loop that saves changed price history every minute. Only the first call
+/// starts it.
+pub fn start(data: Arc<RwLock<TypeMap>>) {
+    HISTORY_CHANGES.start("Price history", Duration::from_secs(60), move || {
+        let data = data.clone();
+        async move { persist::<PriceHistoryMap, _>(&data, HISTORY_STORE_KEY).await }
+    });
+}
@@ -0,0 +1,229 @@
+use super::string_option;
+use crate::data::{digestmap::DigestMap, marketdatamap::MarketDataMap};
+use crate::ronn_utils::store::persist;
+use crate::scheduler::{self, digest, parse_schedule, DigestSchedule, MAX_TRACKED};
+use chrono::Utc;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::{
+    ApplicationCommandInteraction, CommandDataOptionValue,
+};
+use serenity::model::Permissions;
+use serenity::prelude::*;
+use tracing::error;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("digest")
+        .description("Schedule a market digest for this server")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("schedule")
+                .description("Post the digest to a channel on a schedule")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Channel to post the digest in")
+                        .kind(CommandOptionType::Channel)
+                        .required(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("when")
+                        .description("daily, hourly or a cron expression like `0 9 * * *` (UTC)")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("track")
+                .description("Add a token or NFT collection to the digest")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| kind_option(sub))
+                .create_sub_option(|sub| query_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("untrack")
+                .description("Remove a token or NFT collection from the digest")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| kind_option(sub))
+                .create_sub_option(|sub| query_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("show")
+                .description("Show the digest settings for this server")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .create_option(|option| {
+            option
+                .name("now")
+                .description("Post the digest right away")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .create_option(|option| {
+            option
+                .name("disable")
+                .description("Stop posting the digest and forget what is tracked")
+                .kind(CommandOptionType::SubCommand)
+        })
+}
+
+fn kind_option(
+    sub: &mut serenity::builder::CreateApplicationCommandOption,
+) -> &mut serenity::builder::CreateApplicationCommandOption {
+    sub.name("kind")
+        .description("What to track")
+        .kind(CommandOptionType::String)
+        .add_string_choice("token", "token")
+        .add_string_choice("collection", "collection")
+        .required(true)
+}
+
+fn query_option(
+    sub: &mut serenity::builder::CreateApplicationCommandOption,
+) -> &mut serenity::builder::CreateApplicationCommandOption {
+    sub.name("query")
+        .description("Token search or collection name")
+        .kind(CommandOptionType::String)
+        .required(true)
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) -> String {
+    let Some(guild_id) = command.guild_id else {
+        return "Digests can only be scheduled inside a server".to_string();
+    };
+    let Some(subcommand) = command.data.options.first() else {
+        return "Please pick a digest subcommand".to_string();
+    };
+    let schedules = ctx
+        .data
+        .read()
+        .await
+        .get::<DigestMap>()
+        .expect("Expected DigestMap in TypeMap.")
+        .clone();
+
+    let reply = {
+        let mut schedules = schedules.write().await;
+        match subcommand.name.as_str() {
+            "schedule" => {
+                let channel = subcommand
+                    .options
+                    .iter()
+                    .find_map(|option| match &option.resolved {
+                        Some(CommandDataOptionValue::Channel(channel)) => Some(channel.id),
+                        _ => None,
+                    });
+                let when = string_option(&subcommand.options, "when").unwrap_or_default();
+                let (Some(channel_id), Ok(_)) = (channel, parse_schedule(&when)) else {
+                    return format!(
+                        "`{}` is not a valid schedule, use daily, hourly or a cron expression",
+                        when
+                    );
+                };
+                let schedule = schedules.entry(guild_id.0).or_default();
+                schedule.channel_id = channel_id.0;
+                schedule.cron = when;
+                schedule.last_run = Some(Utc::now());
+                format!(
+                    "Digest will be posted in <#{}>, next run {}",
+                    channel_id,
+                    next_run_text(schedule)
+                )
+            }
+            "track" | "untrack" => {
+                let kind = string_option(&subcommand.options, "kind").unwrap_or_default();
+                let query = string_option(&subcommand.options, "query").unwrap_or_default();
+                let schedule = schedules.entry(guild_id.0).or_default();
+                let list = if kind == "token" {
+                    &mut schedule.tokens
+                } else {
+                    &mut schedule.collections
+                };
+                let existing = list.iter().position(|q| q.eq_ignore_ascii_case(&query));
+                match (subcommand.name.as_str(), existing) {
+                    ("track", Some(_)) => format!("`{}` is already tracked", query),
+                    ("track", None) if list.len() >= MAX_TRACKED => {
+                        format!("You can only track {} of each kind", MAX_TRACKED)
+                    }
+                    ("track", None) => {
+                        list.push(query.clone());
+                        format!("Now tracking {} `{}`", kind, query)
+                    }
+                    (_, Some(index)) => {
+                        list.remove(index);
+                        format!("Stopped tracking {} `{}`", kind, query)
+                    }
+                    (_, None) => format!("`{}` is not tracked", query),
+                }
+            }
+            "show" => match schedules.get(&guild_id.0) {
+                Some(schedule) => describe(schedule),
+                None => "No digest is set up for this server".to_string(),
+            },
+            "now" => match schedules.get(&guild_id.0) {
+                Some(schedule) if schedule.channel_id != 0 => {
+                    let schedule = schedule.clone();
+                    drop(schedules);
+                    let market = ctx
+                        .data
+                        .read()
+                        .await
+                        .get::<MarketDataMap>()
+                        .expect("Expected MarketDataMap in TypeMap.")
+                        .clone();
+                    return match digest::post(&ctx.http, &market, guild_id, &schedule).await {
+                        Ok(()) => format!("Digest posted in <#{}>", schedule.channel_id),
+                        Err(why) => format!("Digest could not be posted: {}", why),
+                    };
+                }
+                _ => "Set a channel first with /digest schedule".to_string(),
+            },
+            "disable" => match schedules.remove(&guild_id.0) {
+                Some(_) => "Digest disabled".to_string(),
+                None => "No digest is set up for this server".to_string(),
+            },
+            _ => "Unknown digest subcommand".to_string(),
+        }
+    };
+
+    if let Err(why) = persist::<DigestMap, _>(&ctx.data, scheduler::STORE_KEY).await {
+        error!("Digest schedules could not be saved: {}", why);
+        return format!("{}\n(warning: settings could not be saved)", reply);
+    }
+    reply
+}
+
+fn next_run_text(schedule: &DigestSchedule) -> String {
+    match schedule.next_run() {
+        Some(next) => format!("<t:{}:R>", next.timestamp()),
+        None => "never".to_string(),
+    }
+}
+
+fn describe(schedule: &DigestSchedule) -> String {
+    let list = |items: &Vec<String>| {
+        if items.is_empty() {
+            "none".to_string()
+        } else {
+            items.join(", ")
+        }
+    };
+    let channel = if schedule.channel_id == 0 {
+        "not set".to_string()
+    } else {
+        format!("<#{}>", schedule.channel_id)
+    };
+    format!(
+        "Channel: {}\nSchedule: `{}` (next run {})\nTokens: {}\nCollections: {}",
+        channel,
+        schedule.cron,
+        next_run_text(schedule),
+        list(&schedule.tokens),
+        list(&schedule.collections)
+    )
+}
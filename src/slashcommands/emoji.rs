@@ -0,0 +1,311 @@
+use super::string_option;
+use crate::commands::steal::{self, emoji_name, slot_limit, StealRequest, MAX_EMOJI_BYTES};
+use crate::data::{apiclientmap::ApiClientMap, emojistatsmap::EmojiStatsMap};
+use crate::emojistats;
+use crate::ronn_utils::responder::CommandResponse;
+use chrono::Utc;
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommandOption};
+use serenity::model::guild::{Emoji, PremiumTier};
+use serenity::model::prelude::command::{CommandOptionType, CommandType};
+use serenity::model::prelude::interaction::application_command::{
+    ApplicationCommandInteraction, CommandDataOptionValue,
+};
+use serenity::model::Permissions;
+use serenity::prelude::*;
+use serenity::utils::parse_emoji;
+use std::io::{Cursor, Write};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+/// Name of the message context menu command.
+pub const STEAL_CONTEXT_MENU: &str = "Steal emojis";
+/// Attachment limit for bots.
+pub const MAX_EXPORT_BYTES: usize = 25 * 1024 * 1024;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("emoji")
+        .description("Manage the emojis of this server")
+        .default_member_permissions(Permissions::MANAGE_EMOJIS_AND_STICKERS)
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("rename")
+                .description("Rename an emoji")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| emoji_option(sub))
+                .create_sub_option(|sub| {
+                    sub.name("name")
+                        .description("New name")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("delete")
+                .description("Delete an emoji")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| emoji_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("info")
+                .description("Show details about an emoji")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| emoji_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("list")
+                .description("List the emojis and how many slots are used")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .create_option(|option| {
+            option
+                .name("stats")
+                .description("Show the most and least used emojis")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("days")
+                        .description("Only count the last few days")
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(1)
+                        .max_int_value(emojistats::RETENTION_DAYS)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("export")
+                .description("Download every emoji as a zip file")
+                .kind(CommandOptionType::SubCommand)
+        })
+}
+
+pub fn register_steal(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name(STEAL_CONTEXT_MENU)
+        .kind(CommandType::Message)
+        .default_member_permissions(Permissions::MANAGE_EMOJIS_AND_STICKERS)
+        .dm_permission(false)
+}
+
+fn emoji_option(sub: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    sub.name("emoji")
+        .description("The emoji itself or its name")
+        .kind(CommandOptionType::String)
+        .required(true)
+}
+
+/// Finds an emoji by `<:name:id>`, exact name or, failing that, name in any case.
+pub fn find_emoji<'a>(emojis: &'a [Emoji], query: &str) -> Option<&'a Emoji> {
+    let query = query.trim().trim_matches(':');
+    if let Some(parsed) = parse_emoji(query) {
+        return emojis.iter().find(|emoji| emoji.id == parsed.id);
+    }
+    emojis.iter().find(|emoji| emoji.name == query).or_else(|| {
+        emojis
+            .iter()
+            .find(|emoji| emoji.name.eq_ignore_ascii_case(query))
+    })
+}
+
+pub fn slot_usage(emojis: &[Emoji], tier: PremiumTier) -> String {
+    let limit = slot_limit(tier);
+    let animated = emojis.iter().filter(|emoji| emoji.animated).count();
+    format!(
+        "Static {}/{}, animated {}/{}",
+        emojis.len() - animated,
+        limit,
+        animated,
+        limit
+    )
+}
+
+pub fn list(emojis: &[Emoji], tier: PremiumTier) -> String {
+    let mut sorted: Vec<&Emoji> = emojis.iter().collect();
+    sorted.sort_by_key(|emoji| emoji.name.to_lowercase());
+    let mut reply = slot_usage(emojis, tier);
+    reply.push('\n');
+    for emoji in sorted {
+        let entry = format!("{} ", emoji);
+        // Keep room for the ellipsis within the message limit
+        if reply.len() + entry.len() > 1990 {
+            reply.push_str("...");
+            break;
+        }
+        reply.push_str(&entry);
+    }
+    reply.trim_end().to_string()
+}
+
+pub fn info(emoji: &Emoji) -> String {
+    let added_by = match &emoji.user {
+        Some(user) => format!("<@{}>", user.id),
+        None => "unknown".to_string(),
+    };
+    format!(
+        "{} `{}`\nId: {}\nAnimated: {}\nAdded by: {}\nCreated: <t:{}:D>\n{}",
+        emoji,
+        emoji.name,
+        emoji.id,
+        if emoji.animated { "yes" } else { "no" },
+        added_by,
+        emoji.id.created_at().unix_timestamp(),
+        emoji.url()
+    )
+}
+
+/// Zips `(filename, data)` pairs in memory.
+pub fn zip_files(files: &[(String, Vec<u8>)]) -> zip::result::ZipResult<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(vec![]));
+    for (filename, data) in files {
+        writer.start_file(filename, FileOptions::default())?;
+        writer.write_all(data)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+/// `name.png` or `name.gif`, with the id added when two emojis share a name.
+pub fn export_filename(emoji: &Emoji, taken: &[String]) -> String {
+    let extension = if emoji.animated { "gif" } else { "png" };
+    let filename = format!("{}.{}", emoji.name, extension);
+    if taken.contains(&filename) {
+        format!("{}-{}.{}", emoji.name, emoji.id, extension)
+    } else {
+        filename
+    }
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) -> CommandResponse {
+    let Some(guild_id) = command.guild_id else {
+        return CommandResponse::text("Emojis can only be managed inside a server");
+    };
+    let Some(subcommand) = command.data.options.first() else {
+        return CommandResponse::text("Please pick an emoji subcommand");
+    };
+    let guild = match guild_id.to_partial_guild(&ctx.http).await {
+        Ok(guild) => guild,
+        Err(why) => return CommandResponse::text(format!("Could not load the server: {}", why)),
+    };
+    let emojis: Vec<Emoji> = guild.emojis.into_values().collect();
+    let query = string_option(&subcommand.options, "emoji").unwrap_or_default();
+
+    match subcommand.name.as_str() {
+        "list" => CommandResponse::text(list(&emojis, guild.premium_tier)),
+        "export" => export(ctx, guild_id.0, &emojis).await,
+        "stats" => {
+            let days = subcommand
+                .options
+                .iter()
+                .find_map(|option| match option.resolved {
+                    Some(CommandDataOptionValue::Integer(days)) => Some(days),
+                    _ => None,
+                });
+            let stats = ctx
+                .data
+                .read()
+                .await
+                .get::<EmojiStatsMap>()
+                .expect("Expected EmojiStatsMap in TypeMap.")
+                .clone();
+            let stats = stats.read().await;
+            CommandResponse::text(emojistats::report(
+                &emojis,
+                stats.get(&guild_id.0),
+                days,
+                Utc::now(),
+            ))
+        }
+        name => {
+            let Some(emoji) = find_emoji(&emojis, &query) else {
+                return CommandResponse::text(format!(
+                    "`{}` is not an emoji of this server",
+                    query
+                ));
+            };
+            let reply = match name {
+                "info" => info(emoji),
+                "rename" => {
+                    let new_name =
+                        emoji_name(&string_option(&subcommand.options, "name").unwrap_or_default());
+                    match guild_id.edit_emoji(&ctx.http, emoji.id, &new_name).await {
+                        Ok(renamed) => {
+                            format!("Renamed `{}` to {} `{}`", emoji.name, renamed, new_name)
+                        }
+                        Err(why) => format!("Could not rename `{}`: {}", emoji.name, why),
+                    }
+                }
+                "delete" => match guild_id.delete_emoji(&ctx.http, emoji.id).await {
+                    Ok(()) => format!("Deleted `{}`", emoji.name),
+                    Err(why) => format!("Could not delete `{}`: {}", emoji.name, why),
+                },
+                _ => "Unknown emoji subcommand".to_string(),
+            };
+            CommandResponse::text(reply)
+        }
+    }
+}
+
+async fn export(ctx: &Context, guild_id: u64, emojis: &[Emoji]) -> CommandResponse {
+    if emojis.is_empty() {
+        return CommandResponse::text("This server has no emojis");
+    }
+    let client = ctx
+        .data
+        .read()
+        .await
+        .get::<ApiClientMap>()
+        .expect("Expected ApiClientMap in TypeMap.")
+        .clone();
+    let mut files: Vec<(String, Vec<u8>)> = vec![];
+    let mut failed = vec![];
+    for emoji in emojis {
+        match client.get_bytes(&emoji.url(), MAX_EMOJI_BYTES).await {
+            Ok(data) => {
+                let taken: Vec<String> = files.iter().map(|(name, _)| name.clone()).collect();
+                files.push((export_filename(emoji, &taken), data));
+            }
+            Err(why) => failed.push(format!("`{}` ({})", emoji.name, why)),
+        }
+    }
+    let archive = match zip_files(&files) {
+        Ok(archive) if archive.len() <= MAX_EXPORT_BYTES => archive,
+        Ok(_) => return CommandResponse::text("The export is too large to upload"),
+        Err(why) => return CommandResponse::text(format!("Could not build the zip: {}", why)),
+    };
+    let mut content = format!("Exported {} emojis", files.len());
+    if !failed.is_empty() {
+        content.push_str(&format!(", could not download {}", failed.join(", ")));
+    }
+    CommandResponse::text(content).with_attachment(format!("emojis-{}.zip", guild_id), archive)
+}
+
+/// Handles the "Steal emojis" context menu on a message.
+pub async fn run_steal(ctx: &Context, command: &ApplicationCommandInteraction) -> CommandResponse {
+    let Some(guild_id) = command.guild_id else {
+        return CommandResponse::text("Emojis can only be added inside a server");
+    };
+    let message = command
+        .data
+        .target_id
+        .and_then(|id| command.data.resolved.messages.get(&id.to_message_id()));
+    let Some(message) = message else {
+        return CommandResponse::text("Could not read that message");
+    };
+    let request = StealRequest::from_text(&message.content);
+    if request.sources.is_empty() {
+        return CommandResponse::text("That message has no custom emojis");
+    }
+    let client = ctx
+        .data
+        .read()
+        .await
+        .get::<ApiClientMap>()
+        .expect("Expected ApiClientMap in TypeMap.")
+        .clone();
+    match steal::add_emojis(&ctx.http, &client, guild_id, request).await {
+        Ok(response) => response,
+        Err(why) => CommandResponse::text(format!("Could not add the emojis: {}", why)),
+    }
+}
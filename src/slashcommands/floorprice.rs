@@ -1,13 +1,12 @@
-use anyhow::{anyhow, Result};
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use reqwest::StatusCode;
+use super::{bool_option, string_option};
+use crate::providers::MarketData;
+use crate::ronn_utils::apiclient::ApiError;
+use crate::ronn_utils::cache::Cached;
 use serde::{Deserialize, Serialize};
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::{
-    CommandDataOption, CommandDataOptionValue,
-};
-use std::env;
+use serenity::model::prelude::interaction::application_command::CommandDataOption;
+use serenity::model::prelude::GuildId;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +21,22 @@ pub struct Collection {
     pub floor_ask: FloorAsk,
     pub primary_contract: String,
     pub image: String,
+    #[serde(default)]
+    pub volume: Option<CollectionVolume>,
+    #[serde(default)]
+    pub floor_sale_change: Option<FloorSaleChange>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollectionVolume {
+    #[serde(rename = "1day")]
+    pub one_day: Option<f64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FloorSaleChange {
+    #[serde(rename = "1day")]
+    pub one_day: Option<f64>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -45,60 +60,60 @@ pub struct Amount {
     pub native: f64,
 }
 
-pub async fn run(options: &[CommandDataOption]) -> String {
-    let option = options
-        .get(0)
-        .expect("Expected Collection Name")
-        .resolved
-        .as_ref()
-        .expect("Expected Collection Object");
-    let verbose_flag = match options.get(1) {
-        Some(verb) => verb.value.as_ref().unwrap().as_bool().unwrap(),
-        None => false,
+/// The `/floorprice` reply, Err when the lookup failed.
+pub async fn run(
+    market: &MarketData,
+    guild_id: Option<GuildId>,
+    options: &[CommandDataOption],
+) -> Result<String, String> {
+    let Some(collection) = string_option(options, "project") else {
+        return Err("Please Provide a collection name".to_string());
     };
-
-    if let CommandDataOptionValue::String(collection) = option {
-        let api_result = call_api(collection).await;
-        let mut aggregated_output = String::new();
-        match api_result {
-            Ok(api_output) => {
-                let result_length = api_output.collections.len() as u32;
-                if result_length == 0 {
-                    return format!("There is no collection found for the name {} ", collection);
-                }
-                if verbose_flag {
-                    for project in &api_output.collections {
-                        let proj_name = &project.name;
-                        //let floor_price = &project.floor_ask.price.clone().unwrap().amount.decimal;
-                        let floor_price = match &project.floor_ask.price {
-                            Some(p) => p.amount.decimal,
-                            None => 0.0,
-                        };
-                        let floor_source = &project.floor_ask.source_domain;
-                        let temp_string = format!(
-                            "The floor price for [{}] is [{}]ETH and is on [{}]\n",
-                            proj_name, floor_price, floor_source
-                        );
-                        aggregated_output.push_str(&temp_string);
-                    }
-                    aggregated_output
-                } else {
-                    let floor_price = match &api_output.collections[0].floor_ask.price {
+    let verbose_flag = bool_option(options, "verbose").unwrap_or(false);
+    let api_result = call_api(market, guild_id, &collection).await;
+    let mut aggregated_output = String::new();
+    match api_result {
+        Ok(cached) => {
+            let api_output = &cached.value;
+            let result_length = api_output.collections.len() as u32;
+            if result_length == 0 {
+                return Ok(format!(
+                    "There is no collection found for the name {} ",
+                    collection
+                ));
+            }
+            let body = if verbose_flag {
+                for project in &api_output.collections {
+                    let proj_name = &project.name;
+                    //let floor_price = &project.floor_ask.price.clone().unwrap().amount.decimal;
+                    let floor_price = match &project.floor_ask.price {
                         Some(p) => p.amount.decimal,
                         None => 0.0,
                     };
-                    let project_name = &api_output.collections[0].name;
-                    let floor_source = &api_output.collections[0].floor_ask.source_domain;
-                    format!(
-                        "The floor price for [{}] is [{}]ETH and is on [{}]",
-                        project_name, floor_price, floor_source
-                    )
+                    let floor_source = &project.floor_ask.source_domain;
+                    let temp_string = format!(
+                        "The floor price for [{}] is [{}]ETH and is on [{}]\n",
+                        proj_name, floor_price, floor_source
+                    );
+                    aggregated_output.push_str(&temp_string);
                 }
-            }
-            Err(_) => "Something went wrong contact izee".to_string(),
+                aggregated_output
+            } else {
+                let floor_price = match &api_output.collections[0].floor_ask.price {
+                    Some(p) => p.amount.decimal,
+                    None => 0.0,
+                };
+                let project_name = &api_output.collections[0].name;
+                let floor_source = &api_output.collections[0].floor_ask.source_domain;
+                format!(
+                    "The floor price for [{}] is [{}]ETH and is on [{}]",
+                    project_name, floor_price, floor_source
+                )
+            };
+            Ok(format!("{}\n_{}_", body, cached.age_text()))
         }
-    } else {
-        "Please Provide a collection name".to_string()
+        Err(why @ ApiError::NotConfigured(_)) => Err(why.to_string()),
+        Err(why) => Err(format!("Something went wrong contact izee: {}", why)),
     }
 }
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -121,36 +136,10 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
         })
 }
 
-pub async fn call_api(nft_collection: &String) -> Result<Root, anyhow::Error> {
-    let url = format!(
-        "https://api.reservoir.tools/collections/v6?name={}",
-        nft_collection
-    );
-    dbg!(&url);
-
-    let mut headers = HeaderMap::new();
-    {
-        let api_key = env::var("RESERVOIR_API_KEY").expect("NO API?");
-        headers.insert(
-            HeaderName::from_static("x-api-key"),
-            HeaderValue::from_str(api_key.as_str()).unwrap(),
-        );
-    }
-
-    headers.insert(
-        HeaderName::from_static("accept"),
-        HeaderValue::from_static("*/*"),
-    );
-
-    let response = reqwest::Client::new()
-        .get(&url)
-        .headers(headers)
-        .send()
-        .await
-        .unwrap();
-    dbg!(&response);
-    match response.status() {
-        StatusCode::OK => Ok(response.json::<Root>().await?),
-        other => Err(anyhow!("Error Contacting API {}", other)),
-    }
+pub async fn call_api(
+    market: &MarketData,
+    guild_id: Option<GuildId>,
+    nft_collection: &str,
+) -> Result<Cached<Root>, ApiError> {
+    market.search_collections(guild_id, nft_collection).await
 }
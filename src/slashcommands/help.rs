@@ -0,0 +1,77 @@
+use super::string_option;
+use crate::access::Caller;
+use crate::commands::help::{self, parse_button, Viewer};
+use crate::ronn_utils::responder::CommandResponse;
+use serenity::builder::CreateApplicationCommand;
+use serenity::framework::standard::CommandGroup;
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::prelude::interaction::message_component::MessageComponentInteraction;
+use serenity::model::prelude::interaction::InteractionResponseType;
+use serenity::prelude::*;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("help")
+        .description("List the commands you can use")
+        .create_option(|option| {
+            option
+                .name("command")
+                .description("Show details for one command")
+                .kind(CommandOptionType::String)
+        })
+}
+
+pub async fn run(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    groups: &[&'static CommandGroup],
+) -> CommandResponse {
+    let query = string_option(&command.data.options, "command").unwrap_or_default();
+    let viewer = Viewer::fetch(
+        ctx,
+        command.guild_id,
+        command.channel_id,
+        command.user.id,
+        command.member.as_ref().map(Caller::from_member),
+    )
+    .await;
+    let entries = help::entries(ctx, groups, &viewer).await;
+    help::response(&entries, &query, command.user.id.0)
+}
+
+/// Turns the page when a help button is clicked by the person who asked.
+pub async fn run_button(
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+    groups: &[&'static CommandGroup],
+) -> serenity::Result<()> {
+    let Some((user_id, page)) = parse_button(&component.data.custom_id) else {
+        return Ok(());
+    };
+    if user_id != component.user.id.0 {
+        return component
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| {
+                        d.content(
+                            "Only the person who asked can turn these pages, use help yourself",
+                        )
+                        .ephemeral(true)
+                    })
+            })
+            .await;
+    }
+    let viewer = Viewer::fetch(
+        ctx,
+        component.guild_id,
+        component.channel_id,
+        component.user.id,
+        component.member.as_ref().map(Caller::from_member),
+    )
+    .await;
+    let entries = help::entries(ctx, groups, &viewer).await;
+    help::page(&entries, page, user_id)
+        .update(&ctx.http, component)
+        .await
+}
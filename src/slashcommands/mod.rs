@@ -1,2 +1,64 @@
+pub mod access;
 pub mod coin;
+pub mod digest;
+pub mod emoji;
 pub mod floorprice;
+pub mod help;
+pub mod settings;
+pub mod stats;
+pub mod tag;
+
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::prelude::interaction::application_command::{
+    CommandDataOption, CommandDataOptionValue,
+};
+
+type Register = fn(&mut CreateApplicationCommand) -> &mut CreateApplicationCommand;
+
+/// Every application command the bot registers, in registration order.
+pub const REGISTRY: &[Register] = &[
+    floorprice::register,
+    coin::register,
+    digest::register,
+    tag::register,
+    access::register,
+    settings::register,
+    stats::register,
+    help::register,
+    emoji::register,
+    emoji::register_steal,
+];
+
+/// The built commands from `REGISTRY`, which help also reads.
+pub fn registry() -> Vec<CreateApplicationCommand> {
+    REGISTRY
+        .iter()
+        .map(|register| {
+            let mut command = CreateApplicationCommand::default();
+            register(&mut command);
+            command
+        })
+        .collect()
+}
+
+/// The value of the boolean option `name`, if it was given.
+pub fn bool_option(options: &[CommandDataOption], name: &str) -> Option<bool> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.resolved {
+            Some(CommandDataOptionValue::Boolean(value)) => Some(*value),
+            _ => None,
+        })
+}
+
+/// The trimmed value of the string option `name`, if it was given.
+pub fn string_option(options: &[CommandDataOption], name: &str) -> Option<String> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| match &option.resolved {
+            Some(CommandDataOptionValue::String(value)) => Some(value.trim().to_string()),
+            _ => None,
+        })
+}
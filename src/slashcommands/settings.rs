@@ -0,0 +1,83 @@
+use super::string_option;
+use crate::data::settingsmap::SettingsMap;
+use crate::ronn_utils::store::persist;
+use crate::settings::{self, parse_prefixes, prefixes, GuildSettings};
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::Permissions;
+use serenity::prelude::*;
+use tracing::error;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("settings")
+        .description("Change how the bot behaves in this server")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("prefix")
+                .description("Show or change the prefixes for ! commands")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("prefixes")
+                        .description("One or more prefixes separated by spaces, e.g. `! ?`")
+                        .kind(CommandOptionType::String)
+                })
+        })
+}
+
+pub fn describe_prefixes(settings: Option<&GuildSettings>) -> String {
+    let prefixes: Vec<String> = prefixes(settings)
+        .iter()
+        .map(|prefix| format!("`{}`", prefix))
+        .collect();
+    format!(
+        "Commands work with {} or by mentioning me",
+        prefixes.join(", ")
+    )
+}
+
+/// Replaces the guild's prefixes, keeping the old ones when `input` is not valid.
+pub fn set_prefixes(settings: &mut GuildSettings, input: &str) -> String {
+    match parse_prefixes(input) {
+        Ok(prefixes) => {
+            settings.prefixes = prefixes;
+            describe_prefixes(Some(settings))
+        }
+        Err(why) => why,
+    }
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) -> String {
+    let Some(guild_id) = command.guild_id else {
+        return "Settings only exist inside a server".to_string();
+    };
+    let Some(subcommand) = command.data.options.first() else {
+        return "Please pick a settings subcommand".to_string();
+    };
+    let settings = ctx
+        .data
+        .read()
+        .await
+        .get::<SettingsMap>()
+        .expect("Expected SettingsMap in TypeMap.")
+        .clone();
+    let input = string_option(&subcommand.options, "prefixes");
+
+    let reply = match (subcommand.name.as_str(), input) {
+        ("prefix", None) => return describe_prefixes(settings.read().await.get(&guild_id.0)),
+        ("prefix", Some(input)) => {
+            let mut settings = settings.write().await;
+            set_prefixes(settings.entry(guild_id.0).or_default(), &input)
+        }
+        _ => return "Unknown settings subcommand".to_string(),
+    };
+
+    if let Err(why) = persist::<SettingsMap, _>(&ctx.data, settings::STORE_KEY).await {
+        error!("Settings could not be saved: {}", why);
+        return format!("{}\n(warning: settings could not be saved)", reply);
+    }
+    reply
+}
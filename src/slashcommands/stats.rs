@@ -0,0 +1,74 @@
+use super::string_option;
+use crate::analytics::{command_report, user_report, PERIODS};
+use crate::data::analyticsmap::AnalyticsMap;
+use crate::ronn_utils::responder::CommandResponse;
+use chrono::Utc;
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommandOption};
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::Permissions;
+use serenity::prelude::*;
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("stats")
+        .description("See which commands get used in this server")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("commands")
+                .description("Most used commands and the ones that fail")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| period_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("users")
+                .description("Members who run the most commands")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| period_option(sub))
+        })
+}
+
+fn period_option(sub: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    sub.name("period")
+        .description("How far back to look, a week by default")
+        .kind(CommandOptionType::String);
+    for (name, _) in PERIODS {
+        sub.add_string_choice(name, name);
+    }
+    sub
+}
+
+/// Days in a period choice, a week when it is missing or unknown.
+pub fn period_days(period: Option<&str>) -> Option<i64> {
+    PERIODS
+        .iter()
+        .find(|(name, _)| Some(*name) == period)
+        .map_or(Some(7), |(_, days)| *days)
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) -> CommandResponse {
+    let Some(guild_id) = command.guild_id else {
+        return CommandResponse::text("Stats only exist inside a server");
+    };
+    let Some(subcommand) = command.data.options.first() else {
+        return CommandResponse::text("Please pick a stats subcommand");
+    };
+    let period = string_option(&subcommand.options, "period");
+    let days = period_days(period.as_deref());
+    let log = ctx
+        .data
+        .read()
+        .await
+        .get::<AnalyticsMap>()
+        .expect("Expected AnalyticsMap in TypeMap.")
+        .clone();
+    let log = log.read().await;
+    match subcommand.name.as_str() {
+        "commands" => CommandResponse::embed(command_report(&log, guild_id.0, days, Utc::now())),
+        "users" => CommandResponse::embed(user_report(&log, guild_id.0, days, Utc::now())),
+        _ => CommandResponse::text("Unknown stats subcommand"),
+    }
+}
@@ -0,0 +1,447 @@
+use super::string_option;
+use crate::bot::is_reserved;
+use crate::customcommands::{self, CustomCommand, CustomCommands};
+use crate::data::{
+    customcommandmap::CustomCommandMap, settingsmap::SettingsMap, storemap::StoreMap,
+};
+use crate::ronn_utils::store::persist;
+use crate::settings;
+use chrono::Utc;
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommandOption};
+use serenity::model::channel::Attachment;
+use serenity::model::prelude::command::CommandOptionType;
+use serenity::model::prelude::interaction::application_command::{
+    ApplicationCommandInteraction, CommandDataOption, CommandDataOptionValue,
+};
+use serenity::model::Permissions;
+use serenity::prelude::*;
+use tracing::{error, warn};
+
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_CONTENT_LEN: usize = 2000;
+/// Regular members can own this many tags per server, moderators are not limited.
+pub const MAX_TAGS_PER_MEMBER: usize = 10;
+pub const MAX_UPLOAD_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Who is running /tag, moderators can manage every tag in the server.
+#[derive(Debug, Clone, Copy)]
+pub struct Caller {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub moderator: bool,
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("tag")
+        .description("Create and manage custom replies for this server")
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("create")
+                .description("Create a new tag, used with the server's prefix")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| name_option(sub))
+                .create_sub_option(|sub| content_option(sub).required(true))
+                .create_sub_option(|sub| attachment_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("edit")
+                .description("Change the content or attachment of a tag")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| name_option(sub))
+                .create_sub_option(|sub| content_option(sub))
+                .create_sub_option(|sub| attachment_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("delete")
+                .description("Delete a tag")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| name_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("info")
+                .description("Show who made a tag and how often it is used")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| name_option(sub))
+        })
+        .create_option(|option| {
+            option
+                .name("list")
+                .description("List the tags available in this server")
+                .kind(CommandOptionType::SubCommand)
+        })
+}
+
+fn name_option(sub: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    sub.name("name")
+        .description("Tag name, letters, digits, - and _")
+        .kind(CommandOptionType::String)
+        .required(true)
+}
+
+fn content_option(sub: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    sub.name("content")
+        .description("What the bot replies with")
+        .kind(CommandOptionType::String)
+}
+
+fn attachment_option(
+    sub: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    sub.name("attachment")
+        .description("An image or file sent with the reply")
+        .kind(CommandOptionType::Attachment)
+}
+
+fn attachment(options: &[CommandDataOption]) -> Option<Attachment> {
+    options.iter().find_map(|option| match &option.resolved {
+        Some(CommandDataOptionValue::Attachment(attachment)) => Some(attachment.clone()),
+        _ => None,
+    })
+}
+
+/// Tag names become prefix commands, so they are kept short and shell friendly.
+pub fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.to_lowercase();
+    if name.is_empty()
+        || name.len() > MAX_NAME_LEN
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Tag names are up to {} letters, digits, - or _",
+            MAX_NAME_LEN
+        ));
+    }
+    if is_reserved(&name) {
+        return Err(format!("`{}` is already a bot command", name));
+    }
+    Ok(name)
+}
+
+fn validate_content(content: &str) -> Result<(), String> {
+    if content.chars().count() > MAX_CONTENT_LEN {
+        return Err(format!(
+            "Tag content can be at most {} characters",
+            MAX_CONTENT_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a new tag can be created before anything is downloaded.
+pub fn check_create(
+    commands: &CustomCommands,
+    caller: &Caller,
+    name: &str,
+    content: &str,
+) -> Result<String, String> {
+    let name = validate_name(name)?;
+    validate_content(content)?;
+    if commands.contains_key(&customcommands::key(Some(caller.guild_id), &name)) {
+        return Err(format!("Tag `{}` already exists", name));
+    }
+    let prefix = format!("{}:", caller.guild_id);
+    let owned = commands
+        .iter()
+        .filter(|(key, tag)| key.starts_with(&prefix) && tag.owner == Some(caller.user_id))
+        .count();
+    if !caller.moderator && owned >= MAX_TAGS_PER_MEMBER {
+        return Err(format!(
+            "You already own {} tags, delete one first",
+            MAX_TAGS_PER_MEMBER
+        ));
+    }
+    Ok(name)
+}
+
+pub fn create(
+    commands: &mut CustomCommands,
+    caller: &Caller,
+    name: &str,
+    content: String,
+    upload: Option<String>,
+) -> Result<String, String> {
+    let name = check_create(commands, caller, name, &content)?;
+    commands.insert(
+        customcommands::key(Some(caller.guild_id), &name),
+        CustomCommand {
+            content: Some(content),
+            uploads: upload.into_iter().collect(),
+            owner: Some(caller.user_id),
+            created_at: Some(Utc::now()),
+            ..Default::default()
+        },
+    );
+    Ok(name)
+}
+
+/// Returns the key of a tag the caller may change. Built-in replies are shared
+/// by every server and can only be changed in the store.
+pub fn check_manage(
+    commands: &CustomCommands,
+    caller: &Caller,
+    name: &str,
+) -> Result<String, String> {
+    let key = customcommands::key(Some(caller.guild_id), name);
+    match commands.get(&key) {
+        Some(tag) if caller.moderator || tag.owner == Some(caller.user_id) => Ok(key),
+        Some(_) => Err(format!(
+            "Only the owner or a moderator can change `{}`",
+            name
+        )),
+        None if commands.contains_key(&customcommands::key(None, name)) => Err(format!(
+            "`{}` is a built-in reply and cannot be changed here",
+            name
+        )),
+        None => Err(format!("There is no tag called `{}`", name)),
+    }
+}
+
+/// Applies an edit and returns the uploads it replaced, so they can be removed.
+pub fn edit(
+    commands: &mut CustomCommands,
+    caller: &Caller,
+    name: &str,
+    content: Option<String>,
+    upload: Option<String>,
+) -> Result<Vec<String>, String> {
+    if content.is_none() && upload.is_none() {
+        return Err("Give a new content or attachment".to_string());
+    }
+    if let Some(content) = &content {
+        validate_content(content)?;
+    }
+    let key = check_manage(commands, caller, name)?;
+    let tag = commands.get_mut(&key).expect("Key was just checked");
+    if content.is_some() {
+        tag.content = content;
+    }
+    Ok(match upload {
+        Some(upload) => std::mem::replace(&mut tag.uploads, vec![upload]),
+        None => vec![],
+    })
+}
+
+pub fn delete(
+    commands: &mut CustomCommands,
+    caller: &Caller,
+    name: &str,
+) -> Result<CustomCommand, String> {
+    let key = check_manage(commands, caller, name)?;
+    Ok(commands.remove(&key).expect("Key was just checked"))
+}
+
+pub fn info(commands: &CustomCommands, guild_id: u64, name: &str) -> String {
+    let Some((_, tag)) = customcommands::find(commands, Some(guild_id), name) else {
+        return format!("There is no tag called `{}`", name);
+    };
+    let owner = match tag.owner {
+        Some(owner) => format!("<@{}>", owner),
+        None => "built-in".to_string(),
+    };
+    let created = match tag.created_at {
+        Some(created_at) => format!("<t:{}:D>", created_at.timestamp()),
+        None => "unknown".to_string(),
+    };
+    format!(
+        "Tag `{}`\nOwner: {}\nCreated: {}\nUses: {}\nAttachments: {}",
+        name.to_lowercase(),
+        owner,
+        created,
+        tag.uses,
+        tag.attachments.len() + tag.uploads.len()
+    )
+}
+
+pub fn list(commands: &CustomCommands, guild_id: u64) -> String {
+    let prefix = format!("{}:", guild_id);
+    let mut own: Vec<&str> = commands
+        .keys()
+        .filter_map(|key| key.strip_prefix(&prefix))
+        .collect();
+    let mut builtin: Vec<&str> = commands
+        .keys()
+        .filter(|key| !key.contains(':'))
+        .map(String::as_str)
+        .collect();
+    own.sort_unstable();
+    builtin.sort_unstable();
+    let names = |names: &[&str]| {
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+    let mut reply = format!(
+        "Server tags: {}\nBuilt-in: {}",
+        names(&own),
+        names(&builtin)
+    );
+    if reply.len() > MAX_CONTENT_LEN {
+        let cut = reply
+            .char_indices()
+            .map(|(i, _)| i)
+            .take_while(|i| *i <= MAX_CONTENT_LEN - 3)
+            .last()
+            .unwrap_or_default();
+        reply.truncate(cut);
+        reply.push_str("...");
+    }
+    reply
+}
+
+/// Uploads are stored as `{guild}-{tag}-{attachment id}-{file}` with anything odd in the
+/// filename replaced. The attachment id keeps a new upload from overwriting a live one.
+pub fn upload_name(caller: &Caller, name: &str, attachment: &Attachment) -> String {
+    let filename: String = attachment
+        .filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "{}-{}-{}-{}",
+        caller.guild_id,
+        name.to_lowercase(),
+        attachment.id,
+        filename
+    )
+}
+
+pub async fn run(ctx: &Context, command: &ApplicationCommandInteraction) -> String {
+    let Some(guild_id) = command.guild_id else {
+        return "Tags can only be used inside a server".to_string();
+    };
+    let Some(subcommand) = command.data.options.first() else {
+        return "Please pick a tag subcommand".to_string();
+    };
+    let caller = Caller {
+        guild_id: guild_id.0,
+        user_id: command.user.id.0,
+        moderator: command
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| permissions.contains(Permissions::MANAGE_MESSAGES)),
+    };
+    let (commands, store, settings) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<CustomCommandMap>()
+                .expect("Expected CustomCommandMap in TypeMap.")
+                .clone(),
+            data.get::<StoreMap>()
+                .expect("Expected StoreMap in TypeMap.")
+                .clone(),
+            data.get::<SettingsMap>()
+                .expect("Expected SettingsMap in TypeMap.")
+                .clone(),
+        )
+    };
+    let prefix = {
+        let settings = settings.read().await;
+        settings::prefixes(settings.get(&caller.guild_id)).remove(0)
+    };
+    let name = string_option(&subcommand.options, "name").unwrap_or_default();
+    let content = string_option(&subcommand.options, "content");
+
+    // Validate before downloading so a bad request never touches the store
+    let check = match subcommand.name.as_str() {
+        "create" => check_create(
+            &*commands.read().await,
+            &caller,
+            &name,
+            content.as_deref().unwrap_or_default(),
+        )
+        .map(|_| ()),
+        "edit" => check_manage(&*commands.read().await, &caller, &name).map(|_| ()),
+        _ => Ok(()),
+    };
+    if let Err(why) = check {
+        return why;
+    }
+    let mut upload = None;
+    if let Some(attachment) = attachment(&subcommand.options) {
+        if attachment.size > MAX_UPLOAD_BYTES {
+            return format!(
+                "Attachments can be at most {} MB",
+                MAX_UPLOAD_BYTES / 1024 / 1024
+            );
+        }
+        let filename = upload_name(&caller, &name, &attachment);
+        let saved = match attachment.download().await {
+            Ok(data) => store
+                .save_file(&filename, &data)
+                .await
+                .map_err(|why| why.to_string()),
+            Err(why) => Err(why.to_string()),
+        };
+        if let Err(why) = saved {
+            return format!("Attachment could not be saved: {}", why);
+        }
+        upload = Some(filename);
+    }
+
+    let (reply, removed) = {
+        let mut commands = commands.write().await;
+        let result = match subcommand.name.as_str() {
+            "create" => create(
+                &mut commands,
+                &caller,
+                &name,
+                content.unwrap_or_default(),
+                upload.clone(),
+            )
+            .map(|name| {
+                (
+                    format!("Tag `{}` created, use it with {}{}", name, prefix, name),
+                    vec![],
+                )
+            }),
+            "edit" => edit(&mut commands, &caller, &name, content, upload.clone())
+                .map(|removed| (format!("Tag `{}` updated", name), removed)),
+            "delete" => delete(&mut commands, &caller, &name)
+                .map(|tag| (format!("Tag `{}` deleted", name), tag.uploads)),
+            "info" => return info(&commands, caller.guild_id, &name),
+            "list" => return list(&commands, caller.guild_id),
+            _ => return "Unknown tag subcommand".to_string(),
+        };
+        match result {
+            Ok(done) => done,
+            Err(why) => {
+                // Someone else raced us, throw the fresh upload away
+                if let Some(upload) = &upload {
+                    let _ = store.remove_file(upload).await;
+                }
+                return why;
+            }
+        }
+    };
+    for filename in removed.iter().filter(|f| Some(*f) != upload.as_ref()) {
+        if let Err(why) = store.remove_file(filename).await {
+            warn!("Tag upload {} could not be removed: {}", filename, why);
+        }
+    }
+
+    if let Err(why) = persist::<CustomCommandMap, _>(&ctx.data, customcommands::STORE_KEY).await {
+        error!("Tags could not be saved: {}", why);
+        return format!("{}\n(warning: tags could not be saved)", reply);
+    }
+    reply
+}
@@ -0,0 +1,171 @@
+use ronnbot_rs::access::{check, default_rule, Caller, Denied, GuildAccess, SETTINGS_COMMAND};
+use ronnbot_rs::customcommands::{self, CustomCommand};
+use ronnbot_rs::slashcommands::access::{
+    describe, known_command, set_channel, set_disabled, set_permission, toggle_role, ChannelMode,
+};
+use serenity::model::Permissions;
+
+const CHANNEL: u64 = 10;
+const OTHER_CHANNEL: u64 = 11;
+const ROLE: u64 = 20;
+
+fn member(roles: &[u64], permissions: Permissions) -> Caller {
+    Caller {
+        roles: roles.to_vec(),
+        permissions,
+    }
+}
+
+#[test]
+fn commands_without_rules_are_allowed() {
+    assert_eq!(check(None, "floor", CHANNEL, None), Ok(()));
+    assert_eq!(
+        check(Some(&GuildAccess::default()), "ping", CHANNEL, None),
+        Ok(())
+    );
+}
+
+#[test]
+fn defaults_require_management_permissions() {
+    assert_eq!(
+        default_rule("reactionroles").permissions,
+        Permissions::MANAGE_ROLES.bits()
+    );
+    let caller = member(&[], Permissions::SEND_MESSAGES);
+
+    assert_eq!(
+        check(None, "steal", CHANNEL, Some(&caller)),
+        Err(Denied::MissingPermissions(
+            "steal".to_string(),
+            Permissions::MANAGE_EMOJIS_AND_STICKERS
+        ))
+    );
+    let manager = member(&[], Permissions::MANAGE_EMOJIS_AND_STICKERS);
+    assert_eq!(check(None, "steal", CHANNEL, Some(&manager)), Ok(()));
+}
+
+#[test]
+fn disabled_commands_are_refused_even_for_admins() {
+    let mut access = GuildAccess::default();
+    set_disabled(&mut access, "floor", true);
+    let admin = member(&[], Permissions::ADMINISTRATOR);
+
+    let denied = check(Some(&access), "Floor", CHANNEL, Some(&admin)).unwrap_err();
+    assert_eq!(denied, Denied::Disabled("floor".to_string()));
+    assert_eq!(denied.to_string(), "`floor` is disabled in this server");
+
+    set_disabled(&mut access, "floor", false);
+    assert_eq!(check(Some(&access), "floor", CHANNEL, None), Ok(()));
+}
+
+#[test]
+fn the_settings_command_cannot_be_locked() {
+    let mut access = GuildAccess::default();
+
+    assert!(set_disabled(&mut access, SETTINGS_COMMAND, true).contains("cannot"));
+    set_channel(&mut access, None, CHANNEL, ChannelMode::Deny);
+    assert_eq!(
+        check(Some(&access), SETTINGS_COMMAND, CHANNEL, None),
+        Ok(())
+    );
+}
+
+#[test]
+fn roles_need_any_and_admins_bypass() {
+    let mut access = GuildAccess::default();
+    toggle_role(&mut access, "floor", ROLE);
+    toggle_role(&mut access, "floor", ROLE + 1);
+    let nobody = member(&[], Permissions::empty());
+
+    assert_eq!(
+        check(Some(&access), "floor", CHANNEL, Some(&nobody)),
+        Err(Denied::MissingRole(
+            "floor".to_string(),
+            vec![ROLE, ROLE + 1]
+        ))
+    );
+    let holder = member(&[ROLE + 1], Permissions::empty());
+    assert_eq!(
+        check(Some(&access), "floor", CHANNEL, Some(&holder)),
+        Ok(())
+    );
+    let admin = member(&[], Permissions::ADMINISTRATOR);
+    assert_eq!(check(Some(&access), "floor", CHANNEL, Some(&admin)), Ok(()));
+
+    toggle_role(&mut access, "floor", ROLE);
+    toggle_role(&mut access, "floor", ROLE + 1);
+    assert_eq!(
+        check(Some(&access), "floor", CHANNEL, Some(&nobody)),
+        Ok(())
+    );
+}
+
+#[test]
+fn permissions_can_be_changed_and_cleared() {
+    let mut access = GuildAccess::default();
+    set_permission(&mut access, "floor", Permissions::MANAGE_MESSAGES);
+    let caller = member(&[], Permissions::SEND_MESSAGES);
+
+    assert!(check(Some(&access), "floor", CHANNEL, Some(&caller)).is_err());
+    set_permission(&mut access, "steal", Permissions::empty());
+    assert_eq!(
+        check(Some(&access), "steal", CHANNEL, Some(&caller)),
+        Ok(())
+    );
+}
+
+#[test]
+fn channel_lists_apply_guild_wide_and_per_command() {
+    let mut access = GuildAccess::default();
+    set_channel(&mut access, None, OTHER_CHANNEL, ChannelMode::Deny);
+    set_channel(&mut access, Some("floor"), CHANNEL, ChannelMode::Allow);
+
+    assert_eq!(
+        check(Some(&access), "ping", OTHER_CHANNEL, None),
+        Err(Denied::Channel("ping".to_string()))
+    );
+    assert_eq!(check(Some(&access), "ping", CHANNEL + 5, None), Ok(()));
+    assert_eq!(check(Some(&access), "floor", CHANNEL, None), Ok(()));
+    assert!(check(Some(&access), "floor", CHANNEL + 5, None).is_err());
+
+    set_channel(&mut access, Some("floor"), CHANNEL, ChannelMode::Clear);
+    assert_eq!(check(Some(&access), "floor", CHANNEL + 5, None), Ok(()));
+}
+
+#[test]
+fn describe_lists_the_rules() {
+    let mut access = GuildAccess::default();
+    set_disabled(&mut access, "ping", true);
+    set_channel(&mut access, None, CHANNEL, ChannelMode::Allow);
+
+    let overview = describe(&access, None);
+    assert!(overview.contains("Allowed channels: <#10>"));
+    assert!(overview.contains("Disabled: `ping`"));
+
+    let steal = describe(&access, Some("steal"));
+    assert!(steal.starts_with("`steal` is enabled"));
+    assert!(steal.contains("Roles: any"));
+}
+
+#[test]
+fn rules_are_only_made_for_real_commands() {
+    let mut commands = customcommands::defaults();
+    commands.insert(
+        customcommands::key(Some(1), "wagmi"),
+        CustomCommand::default(),
+    );
+
+    for name in [
+        "ping",
+        "restart-shard",
+        "help",
+        "floorprice",
+        "access",
+        "ilv",
+        "wagmi",
+    ] {
+        assert!(known_command(&commands, 1, name), "{} is a command", name);
+    }
+    assert!(!known_command(&commands, 1, "floorprize"));
+    assert!(!known_command(&commands, 2, "wagmi"));
+}
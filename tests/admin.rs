@@ -0,0 +1,105 @@
+use ronnbot_rs::commands::admin::{latency_response, shards_response};
+use ronnbot_rs::data::config::Config;
+use ronnbot_rs::metrics::server::{Health, ShardHealth};
+use ronnbot_rs::ronn_utils::responder::CommandResponse;
+use serenity::model::id::RoleId;
+use std::path::PathBuf;
+
+fn health() -> Health {
+    Health {
+        shards: vec![
+            ShardHealth {
+                id: 0,
+                stage: "connected".to_string(),
+                latency_ms: Some(42),
+            },
+            ShardHealth {
+                id: 1,
+                stage: "resuming".to_string(),
+                latency_ms: None,
+            },
+        ],
+    }
+}
+
+#[test]
+fn latency_lists_every_shard() {
+    assert_eq!(
+        latency_response(&health()),
+        CommandResponse::text("Shard 0: 42 ms\nShard 1: no heartbeat yet")
+    );
+    assert_eq!(
+        latency_response(&Health::default()),
+        CommandResponse::text("No shards are running")
+    );
+}
+
+#[test]
+fn shards_counts_connected_runners() {
+    assert_eq!(
+        shards_response(&health()),
+        CommandResponse::text("1 of 2 shards connected\nShard 0: connected\nShard 1: resuming")
+    );
+}
+
+#[test]
+fn builtin_config_loads_without_a_path() {
+    let config = Config::load(None).unwrap();
+
+    assert_eq!(config.reaction_roles().unwrap().len(), config.emotes.len());
+    assert_eq!(config.providers.tokens, ["dexscreener", "geckoterminal"]);
+}
+
+#[test]
+fn example_config_throttles_like_the_builtin_one() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.json");
+    let example = Config::load(Some(&path)).unwrap();
+    let builtin = Config::load(None).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&example.cooldowns).unwrap(),
+        serde_json::to_value(&builtin.cooldowns).unwrap()
+    );
+    assert_eq!(example.providers.tokens, builtin.providers.tokens);
+    assert_eq!(example.providers.nfts, builtin.providers.nfts);
+}
+
+#[test]
+fn config_files_are_read_and_bad_emotes_skipped() {
+    let path = std::env::temp_dir().join(format!("ronnbot-config-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{ "channel_id": 1, "emotes": ["🦜", "<:broken"], "role_ids": [10, 20] }"#,
+    )
+    .unwrap();
+    let config = Config::load(Some(&path)).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let roles: Vec<RoleId> = config
+        .reaction_roles()
+        .unwrap()
+        .into_iter()
+        .map(|(_, role)| role)
+        .collect();
+    assert_eq!(roles, [RoleId(10)]);
+    assert!(config.cooldowns.default.is_empty());
+
+    assert!(Config::load(Some(&PathBuf::from("/nonexistent/config.json"))).is_err());
+}
+
+#[test]
+fn unpaired_reaction_roles_are_an_error() {
+    let path = std::env::temp_dir().join(format!("ronnbot-unpaired-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{ "channel_id": 1, "emotes": ["🦜", "🪙"], "role_ids": [10] }"#,
+    )
+    .unwrap();
+    let error = Config::load(Some(&path)).err().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        error.to_string(),
+        "Config has 2 emotes but 1 role_ids, they are paired in order"
+    );
+}
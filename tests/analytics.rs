@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ronnbot_rs::analytics::{
+    command_report, prune, record, user_report, CommandStats, Invocation, Usage, RETENTION_DAYS,
+};
+use ronnbot_rs::emojistats::day;
+use ronnbot_rs::slashcommands::stats::period_days;
+
+const GUILD: u64 = 1;
+const CHANNEL: u64 = 10;
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap()
+}
+
+fn run(command: &str, user_id: u64, days_ago: i64, duration_ms: u64, success: bool) -> Invocation {
+    Invocation {
+        command: command.to_string(),
+        guild_id: Some(GUILD),
+        channel_id: CHANNEL,
+        user_id,
+        at: now() - Duration::days(days_ago),
+        duration_ms,
+        success,
+    }
+}
+
+fn stats() -> CommandStats {
+    let mut stats = CommandStats::new();
+    for invocation in [
+        run("ilv", 1, 0, 100, true),
+        run("ilv", 2, 0, 300, true),
+        run("ilv", 1, 1, 200, true),
+        run("floorprice", 1, 2, 900, false),
+        run("floorprice", 3, 2, 1100, true),
+        run("coin", 3, 20, 500, false),
+    ] {
+        record(&mut stats, invocation);
+    }
+    stats
+}
+
+#[test]
+fn runs_are_counted_per_day_command_and_user() {
+    let stats = stats();
+    let today = &stats[&GUILD][&day(now())];
+
+    assert_eq!(
+        today["ilv"][&CHANNEL][&1],
+        Usage {
+            uses: 1,
+            errors: 0,
+            total_ms: 100
+        }
+    );
+    assert_eq!(
+        stats[&GUILD][&day(now() - Duration::days(2))]["floorprice"][&CHANNEL][&1],
+        Usage {
+            uses: 1,
+            errors: 1,
+            total_ms: 900
+        }
+    );
+    assert_eq!(stats[&GUILD].len(), 4);
+}
+
+#[test]
+fn channels_are_kept_apart_but_reported_together() {
+    let mut stats = stats();
+    record(
+        &mut stats,
+        Invocation {
+            channel_id: 11,
+            ..run("ilv", 1, 0, 400, true)
+        },
+    );
+
+    let today = &stats[&GUILD][&day(now())]["ilv"];
+    assert_eq!(today[&CHANNEL][&1].uses, 1);
+    assert_eq!(today[&11][&1].uses, 1);
+    let embed = command_report(&stats, GUILD, Some(1), now());
+    assert_eq!(
+        embed.description.as_deref(),
+        Some("`ilv` 3 uses, 0 failed, avg 266ms")
+    );
+}
+
+#[test]
+fn old_days_are_pruned() {
+    let mut stats = stats();
+    let later = now() + Duration::days(RETENTION_DAYS - 10);
+    prune(&mut stats, later);
+
+    let days: Vec<i64> = stats[&GUILD].keys().copied().collect();
+    assert_eq!(
+        days,
+        vec![
+            day(now() - Duration::days(2)),
+            day(now() - Duration::days(1)),
+            day(now())
+        ]
+    );
+
+    prune(&mut stats, later + Duration::days(30));
+    assert!(stats.is_empty());
+}
+
+#[test]
+fn commands_are_ranked_with_failures() {
+    let embed = command_report(&stats(), GUILD, Some(7), now());
+
+    assert_eq!(
+        embed.title.as_deref(),
+        Some("Command use in the last 7 days")
+    );
+    assert_eq!(
+        embed.description.as_deref(),
+        Some("`ilv` 3 uses, 0 failed, avg 200ms\n`floorprice` 2 uses, 1 failed, avg 1000ms")
+    );
+    assert_eq!(
+        embed.fields,
+        vec![(
+            "Most errors".to_string(),
+            "`floorprice` 1 of 2 (50%)".to_string(),
+            false
+        )]
+    );
+    assert_eq!(embed.footer.as_deref(), Some("5 commands run"));
+
+    let all = command_report(&stats(), GUILD, None, now());
+    assert!(all.description.unwrap().contains("`coin` 1 uses, 1 failed"));
+}
+
+#[test]
+fn other_guilds_and_empty_periods_report_nothing() {
+    let embed = command_report(&stats(), 2, None, now());
+    assert_eq!(embed.description.as_deref(), Some("No commands were used"));
+
+    let mut stats = CommandStats::new();
+    record(
+        &mut stats,
+        Invocation {
+            guild_id: None,
+            ..run("ilv", 1, 0, 100, true)
+        },
+    );
+    assert!(stats.is_empty());
+    let embed = user_report(&stats, GUILD, None, now());
+    assert_eq!(embed.description.as_deref(), Some("No commands were used"));
+}
+
+#[test]
+fn users_are_ranked_with_their_favourite() {
+    let embed = user_report(&stats(), GUILD, Some(1), now());
+
+    assert_eq!(
+        embed.description.as_deref(),
+        Some("1. <@1> 1 commands, mostly `ilv`\n2. <@2> 1 commands, mostly `ilv`")
+    );
+
+    let embed = user_report(&stats(), GUILD, None, now());
+    assert!(embed
+        .description
+        .unwrap()
+        .starts_with("1. <@1> 3 commands, mostly `ilv`\n2. <@3> 2 commands"));
+}
+
+#[test]
+fn periods_default_to_a_week() {
+    assert_eq!(period_days(Some("day")), Some(1));
+    assert_eq!(period_days(Some("all")), None);
+    assert_eq!(period_days(None), Some(7));
+    assert_eq!(period_days(Some("year")), Some(7));
+}
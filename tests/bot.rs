@@ -0,0 +1,97 @@
+use ronnbot_rs::bot::{intents, members_intent_allowed, parse_flags, BotOptions};
+use ronnbot_rs::data::config::{Config, Credentials};
+use serenity::model::application::ApplicationFlags;
+use serenity::prelude::GatewayIntents;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn flags_are_keyed_by_their_environment_variable() {
+    let flags = parse_flags(args(&["--token", "abc", "--config=bot.json"])).unwrap();
+
+    assert_eq!(flags["DISCORD_TOKEN"], "abc");
+    assert_eq!(flags["CONFIG_PATH"], "bot.json");
+    assert_eq!(flags.len(), 2);
+}
+
+#[test]
+fn unknown_or_empty_flags_are_errors() {
+    assert!(parse_flags(args(&["--tokn", "abc"]))
+        .unwrap_err()
+        .to_string()
+        .starts_with("Unknown flag '--tokn'"));
+    assert_eq!(
+        parse_flags(args(&["--images"])).unwrap_err().to_string(),
+        "'--images' needs a value"
+    );
+}
+
+#[test]
+fn options_fill_in_defaults() {
+    let values: HashMap<&str, &str> =
+        [("DISCORD_TOKEN", "abc"), ("RESERVOIR_API_KEY", "key")].into();
+    let options = BotOptions::from_lookup(|key| values.get(key).map(|v| v.to_string())).unwrap();
+
+    assert_eq!(
+        options,
+        BotOptions {
+            token: "abc".to_string(),
+            credentials: Credentials {
+                reservoir_api_key: Some("key".to_string()),
+                opensea_api_key: None,
+            },
+            config_path: None,
+            images_path: PathBuf::from("images"),
+            store_path: PathBuf::from("store"),
+            http_addr: None,
+        }
+    );
+    assert_eq!(
+        BotOptions::from_lookup(|_| None).unwrap_err().to_string(),
+        "'DISCORD_TOKEN' was not found"
+    );
+}
+
+#[test]
+fn missing_secrets_only_disable_their_commands() {
+    let values: HashMap<&str, &str> = [("DISCORD_TOKEN", "abc"), ("RESERVOIR_API_KEY", " ")].into();
+    let options = BotOptions::from_lookup(|key| values.get(key).map(|v| v.to_string())).unwrap();
+
+    assert_eq!(options.credentials, Credentials::default());
+    assert_eq!(
+        options.credentials.missing_for("floorprice").as_deref(),
+        Some("RESERVOIR_API_KEY or OPENSEA_API_KEY")
+    );
+    assert_eq!(options.credentials.missing_for("coin"), None);
+
+    let configured = Credentials {
+        reservoir_api_key: Some("key".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(configured.missing_for("floorprice"), None);
+    let fallback_only = Credentials {
+        opensea_api_key: Some("key".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(fallback_only.missing_for("floorprice"), None);
+}
+
+#[test]
+fn members_intent_is_only_asked_for_greetings() {
+    let allowed = Some(ApplicationFlags::GATEWAY_GUILD_MEMBERS_LIMITED);
+    let mut config = Config::load(None).unwrap();
+    assert!(!intents(&config, allowed).contains(GatewayIntents::GUILD_MEMBERS));
+
+    config.greetings.insert(1, Default::default());
+    assert!(intents(&config, allowed).contains(GatewayIntents::GUILD_MEMBERS));
+    assert!(intents(&config, None).contains(GatewayIntents::GUILD_MEMBERS));
+
+    let missing = Some(ApplicationFlags::GATEWAY_MESSAGE_CONTENT);
+    assert!(!members_intent_allowed(missing));
+    assert!(!intents(&config, missing).contains(GatewayIntents::GUILD_MEMBERS));
+    assert!(intents(&config, missing).contains(GatewayIntents::GUILD_MESSAGES));
+}
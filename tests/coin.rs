@@ -0,0 +1,350 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{bool_option, market, serve, string_option, DEXSCREENER_SEARCH};
+use ronnbot_rs::data::config::ProviderConfig;
+use ronnbot_rs::providers::{dexscreener::DexScreener, geckoterminal::GeckoTerminal, MarketData};
+use ronnbot_rs::ronn_utils::apiclient::{retry_after, ApiClient};
+use ronnbot_rs::ronn_utils::responder::CommandResponse;
+use ronnbot_rs::slashcommands::coin;
+use serenity::utils::Colour;
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn returns_the_first_pair() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(DEXSCREENER_SEARCH))
+        .and(query_param("q", "pepe coin"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(common::fixture("dexscreener_search.json")),
+        )
+        .mount(&server)
+        .await;
+
+    let result = coin::run(
+        &market(&server),
+        None,
+        &[string_option("coinname", "pepe coin")],
+    )
+    .await
+    .expect("Lookup should succeed");
+    let pair = &result.value.pairs[0];
+
+    assert_eq!(result.value.pairs.len(), 2);
+    assert_eq!(pair.base_token.name, "Pepe");
+    assert_eq!(pair.price_usd.as_deref(), Some("0.000001362"));
+    assert_eq!(pair.price_change.h24, 5.67);
+    assert_eq!(pair.liquidity.clone().unwrap_or_default().usd, 31234567.89);
+    assert!(!result.stale);
+
+    let embed = coin::response(&result, None).embed.unwrap();
+    assert_eq!(embed.title.as_deref(), Some("Pepe"));
+    assert_eq!(
+        embed.fields[0],
+        (
+            "Price".to_string(),
+            "$0.000001362 : 5.67%".to_string(),
+            true
+        )
+    );
+}
+
+#[tokio::test]
+async fn empty_results_are_an_error() {
+    let server = MockServer::start().await;
+    serve(&server, DEXSCREENER_SEARCH, 200, "dexscreener_empty.json").await;
+
+    let error = coin::run(&market(&server), None, &[string_option("coinname", "nope")])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        coin::error_response(&error),
+        CommandResponse::text("Some Error occured Contact Izeeyahmon.eth: No pair exists")
+    );
+}
+
+#[tokio::test]
+async fn missing_options_are_an_error() {
+    let server = MockServer::start().await;
+
+    let error = coin::run(&market(&server), None, &[bool_option("coinname", true)])
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.to_string(), "Please Provide a coin");
+}
+
+#[tokio::test]
+async fn not_found_is_reported() {
+    let server = MockServer::start().await;
+    serve(&server, DEXSCREENER_SEARCH, 404, "dexscreener_empty.json").await;
+
+    let error = coin::run(&market(&server), None, &[string_option("coinname", "pepe")])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        coin::error_response(&error),
+        CommandResponse::text("Some Error occured Contact Izeeyahmon.eth: Nothing was found")
+    );
+}
+
+#[tokio::test]
+async fn rate_limits_with_a_long_retry_after_fail_fast() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(DEXSCREENER_SEARCH))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "120"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let error = coin::run(&market(&server), None, &[string_option("coinname", "pepe")])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        coin::error_response(&error),
+        CommandResponse::text(
+            "Some Error occured Contact Izeeyahmon.eth: API is rate limited, try again in 120s"
+        )
+    );
+}
+
+#[tokio::test]
+async fn rate_limits_are_retried_after_the_given_delay() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(DEXSCREENER_SEARCH))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    serve(&server, DEXSCREENER_SEARCH, 200, "dexscreener_search.json").await;
+
+    let result = coin::run(&market(&server), None, &[string_option("coinname", "pepe")])
+        .await
+        .expect("Second attempt should succeed");
+
+    assert_eq!(result.value.pairs[0].base_token.symbol, "PEPE");
+}
+
+#[test]
+fn retry_after_reads_seconds_and_dates() {
+    let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+
+    assert_eq!(retry_after(" 120 ", now), Some(Duration::from_secs(120)));
+    assert_eq!(
+        retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+        retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(retry_after("soon", now), None);
+}
+
+#[tokio::test]
+async fn rate_limits_with_a_past_retry_date_are_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(DEXSCREENER_SEARCH))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT"),
+        )
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    serve(&server, DEXSCREENER_SEARCH, 200, "dexscreener_search.json").await;
+
+    let result = coin::run(&market(&server), None, &[string_option("coinname", "pepe")])
+        .await
+        .expect("Second attempt should succeed");
+
+    assert_eq!(result.value.pairs[0].base_token.symbol, "PEPE");
+}
+
+#[tokio::test]
+async fn malformed_json_is_a_decode_error() {
+    let server = MockServer::start().await;
+    serve(&server, DEXSCREENER_SEARCH, 200, "malformed.json").await;
+
+    let error = coin::run(&market(&server), None, &[string_option("coinname", "pepe")])
+        .await
+        .unwrap_err();
+
+    assert!(coin::error_response(&error)
+        .content
+        .unwrap()
+        .starts_with("Some Error occured Contact Izeeyahmon.eth: API answer could not be parsed"));
+}
+
+#[tokio::test]
+async fn missing_price_usd_still_parses() {
+    let server = MockServer::start().await;
+    serve(
+        &server,
+        DEXSCREENER_SEARCH,
+        200,
+        "dexscreener_no_price.json",
+    )
+    .await;
+
+    let result = coin::run(&market(&server), None, &[string_option("coinname", "rug")])
+        .await
+        .expect("Lookup should succeed");
+
+    assert_eq!(result.value.pairs[0].price_usd, None);
+    assert_eq!(result.value.pairs[0].liquidity, None);
+
+    let embed = coin::response(&result, None).embed.unwrap();
+    assert_eq!(
+        embed.fields[1],
+        ("Liquidity".to_string(), "$0".to_string(), false)
+    );
+}
+
+#[tokio::test]
+async fn repeated_queries_are_served_from_cache() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(DEXSCREENER_SEARCH))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(common::fixture("dexscreener_search.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let market = market(&server);
+
+    coin::run(&market, None, &[string_option("coinname", "PEPE")])
+        .await
+        .expect("First lookup should succeed");
+    let cached = coin::run(&market, None, &[string_option("coinname", " pepe ")])
+        .await
+        .expect("Second lookup should hit the cache");
+
+    assert_eq!(cached.value.pairs[0].base_token.symbol, "PEPE");
+}
+
+#[tokio::test]
+async fn falls_back_to_the_next_provider() {
+    let broken = MockServer::start().await;
+    serve(&broken, DEXSCREENER_SEARCH, 404, "dexscreener_empty.json").await;
+    let working = MockServer::start().await;
+    serve(&working, DEXSCREENER_SEARCH, 200, "dexscreener_search.json").await;
+    let market = MarketData::new(
+        Arc::new(ApiClient::default()),
+        vec![
+            Arc::new(DexScreener::with_base_url(broken.uri())),
+            Arc::new(GeckoTerminal::with_base_url(broken.uri())),
+            Arc::new(DexScreener::with_base_url(working.uri())),
+        ],
+        vec![],
+        ProviderConfig::default(),
+    );
+
+    let result = coin::run(&market, None, &[string_option("coinname", "pepe")])
+        .await
+        .expect("Working provider should answer");
+
+    assert_eq!(result.value.pairs[0].base_token.symbol, "PEPE");
+}
+
+#[tokio::test]
+async fn renders_the_coin_embed() {
+    let server = MockServer::start().await;
+    serve(&server, DEXSCREENER_SEARCH, 200, "dexscreener_search.json").await;
+    let result = coin::run(&market(&server), None, &[string_option("coinname", "pepe")])
+        .await
+        .unwrap();
+
+    let response = coin::response(&result, Some(vec![1, 2, 3]));
+    let embed = response.embed.expect("Coin replies with an embed");
+
+    assert_eq!(embed.title.as_deref(), Some("Pepe"));
+    assert_eq!(
+        embed.fields,
+        vec![
+            (
+                "Price".to_string(),
+                "$0.000001362 : 5.67%".to_string(),
+                true
+            ),
+            (
+                "Liquidity".to_string(),
+                "$31 234 567 .89".to_string(),
+                false
+            ),
+            ("Chain".to_string(), "$ethereum@uniswap".to_string(), false),
+            ("VOL".to_string(), "$18 234 567 .12".to_string(), false),
+        ]
+    );
+    assert_eq!(embed.colour, Some(Colour::DARK_GREEN));
+    assert_eq!(embed.footer.as_deref(), Some("Live data"));
+    assert_eq!(embed.image.as_deref(), Some("attachment://chart.png"));
+    assert_eq!(response.attachments[0].filename, "chart.png");
+}
+
+#[tokio::test]
+async fn rugged_coins_say_so() {
+    let server = MockServer::start().await;
+    serve(
+        &server,
+        DEXSCREENER_SEARCH,
+        200,
+        "dexscreener_no_price.json",
+    )
+    .await;
+    let result = coin::run(&market(&server), None, &[string_option("coinname", "rug")])
+        .await
+        .unwrap();
+
+    let embed = coin::response(&result, None).embed.unwrap();
+
+    assert_eq!(embed.fields[0].1, "$No value you got rugged bruh : -99.9%");
+    assert_eq!(embed.colour, Some(Colour::DARK_RED));
+    assert_eq!(embed.image, None);
+}
+
+#[test]
+fn price_history_is_capped_per_pair() {
+    let mut history = coin::PriceHistory::new();
+    let now = 1_700_000_000;
+
+    assert!(coin::record_price(&mut history, "0xpair", 1.0, now));
+    // Too soon after the last sample, nothing to save
+    assert!(!coin::record_price(&mut history, "0xpair", 2.0, now + 10));
+    for step in 1..=coin::HISTORY_SAMPLES as i64 + 20 {
+        coin::record_price(&mut history, "0xpair", 1.0, now + step * 300);
+    }
+
+    assert_eq!(history["0xpair"].len(), coin::HISTORY_SAMPLES);
+}
+
+#[test]
+fn idle_and_excess_pairs_are_evicted() {
+    let mut history = coin::PriceHistory::new();
+    let now = 1_700_000_000;
+
+    coin::record_price(&mut history, "idle", 1.0, now - 2 * 24 * 60 * 60);
+    for pair in 0..coin::HISTORY_PAIRS {
+        coin::record_price(&mut history, &pair.to_string(), 1.0, now + pair as i64);
+    }
+    assert!(!history.contains_key("idle"));
+    assert_eq!(history.len(), coin::HISTORY_PAIRS);
+
+    coin::record_price(&mut history, "new", 1.0, now + 1_000);
+    assert_eq!(history.len(), coin::HISTORY_PAIRS);
+    assert!(history.contains_key("new"));
+    assert!(!history.contains_key("0"));
+}
@@ -0,0 +1,301 @@
+use ronnbot_rs::commands::{assets, floor, meta, reactionroles};
+use ronnbot_rs::customcommands::template::TemplateContext;
+use ronnbot_rs::customcommands::{self, CustomCommand, CustomEmbed};
+use ronnbot_rs::data::{settingsmap::SettingsMap, storemap::StoreMap};
+use ronnbot_rs::ronn_utils::assets::Assets;
+use ronnbot_rs::ronn_utils::responder::CommandResponse;
+use ronnbot_rs::ronn_utils::store::{persist, Store};
+use ronnbot_rs::settings::{GuildSettings, Settings};
+use serde_json::json;
+use serenity::builder::{
+    CreateInteractionResponseData, CreateInteractionResponseFollowup, CreateMessage,
+};
+use serenity::prelude::{RwLock, TypeMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn images() -> Assets {
+    Assets::index(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("images"))
+}
+
+fn store(name: &str) -> (PathBuf, Store) {
+    let dir = std::env::temp_dir().join(format!("ronnbot-{}-{}", name, std::process::id()));
+    let store = Store::open(&dir).unwrap();
+    (dir, store)
+}
+
+#[test]
+fn text_replies() {
+    assert_eq!(meta::ping_response(), CommandResponse::text("Pong!"));
+    assert!(floor::floor_response()
+        .content
+        .unwrap()
+        .contains("/floorprice"));
+}
+
+#[test]
+fn default_custom_commands_cover_the_old_memes() {
+    let commands = customcommands::defaults();
+
+    for name in [
+        "izee",
+        "josh",
+        "swypes",
+        "zyo",
+        "ziz",
+        "flipcreed",
+        "absinthe",
+        "zilbag",
+        "ilv",
+        "gm",
+        "gn",
+        "fraggy",
+        "panels",
+    ] {
+        assert!(commands.contains_key(name), "missing {}", name);
+    }
+    assert_eq!(
+        commands["gm"].content.as_deref(),
+        Some("GM <a:sykablyatdance:956543246560002108>")
+    );
+    assert!(commands["ilv"].content.as_ref().unwrap().len() < 2000);
+}
+
+#[tokio::test]
+async fn text_custom_commands_need_no_files() {
+    let response = customcommands::defaults()["izee"]
+        .response(
+            &images(),
+            &store("responses").1,
+            &TemplateContext::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response, CommandResponse::text("My Master aint no Simp!"));
+}
+
+#[tokio::test]
+async fn file_custom_commands_attach_the_image() {
+    let response = customcommands::defaults()["panels"]
+        .response(
+            &images(),
+            &store("responses").1,
+            &TemplateContext::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.content.as_deref(), Some("Jves Rug"));
+    assert_eq!(response.attachments.len(), 1);
+    assert_eq!(response.attachments[0].filename, "panels.jpg");
+    assert!(!response.attachments[0].data.is_empty());
+}
+
+#[tokio::test]
+async fn missing_images_are_an_error() {
+    let error = customcommands::defaults()["ziz"]
+        .response(
+            &images(),
+            &store("responses").1,
+            &TemplateContext::default(),
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[tokio::test]
+async fn embed_images_from_the_folder_are_attached() {
+    let command = CustomCommand {
+        embed: Some(CustomEmbed {
+            title: Some("Rug".to_string()),
+            image: Some("panels.jpg".to_string()),
+            colour: Some(0xff0000),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let response = command
+        .response(
+            &images(),
+            &store("responses").1,
+            &TemplateContext::default(),
+        )
+        .await
+        .unwrap();
+    let embed = response.embed.unwrap();
+
+    assert_eq!(embed.image.as_deref(), Some("attachment://panels.jpg"));
+    assert_eq!(response.attachments[0].filename, "panels.jpg");
+}
+
+#[tokio::test]
+async fn tags_cannot_ping_everyone() {
+    let tag = CustomCommand {
+        content: Some("@everyone free mint <@&5>".to_string()),
+        ..Default::default()
+    };
+    let response = tag
+        .response(
+            &images(),
+            &store("responses").1,
+            &TemplateContext::default(),
+        )
+        .await
+        .unwrap();
+    let users_only = json!({ "parse": ["users"] });
+
+    let mut message = CreateMessage::default();
+    response.apply_message(&mut message);
+    assert_eq!(message.0["content"], json!("@everyone free mint <@&5>"));
+    assert_eq!(message.0["allowed_mentions"], users_only);
+
+    let mut followup = CreateInteractionResponseFollowup::default();
+    response.apply_followup(&mut followup);
+    assert_eq!(followup.0["allowed_mentions"], users_only);
+
+    let mut data = CreateInteractionResponseData::default();
+    response.apply_response(&mut data);
+    assert_eq!(data.0["allowed_mentions"], users_only);
+}
+
+#[tokio::test]
+async fn uploads_are_read_from_the_store() {
+    let (dir, store) = store("uploads");
+    store.save_file("1-wave-wave.gif", b"GIF89a").await.unwrap();
+    let command = CustomCommand {
+        content: Some("wave".to_string()),
+        uploads: vec!["1-wave-wave.gif".to_string()],
+        ..Default::default()
+    };
+
+    let response = command
+        .response(&images(), &store, &TemplateContext::default())
+        .await
+        .unwrap();
+
+    assert_eq!(response.attachments[0].filename, "1-wave-wave.gif");
+    assert_eq!(response.attachments[0].data, b"GIF89a");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn store_is_seeded_then_edits_are_kept() {
+    let (dir, store) = store("custom");
+
+    let mut commands = customcommands::load(&store).await.unwrap();
+    assert_eq!(commands, customcommands::defaults());
+
+    commands.insert(
+        "wagmi".to_string(),
+        CustomCommand {
+            content: Some("WAGMI".to_string()),
+            ..Default::default()
+        },
+    );
+    store
+        .save(customcommands::STORE_KEY, &commands)
+        .await
+        .unwrap();
+
+    let reloaded = customcommands::load(&store).await.unwrap();
+    assert_eq!(reloaded["wagmi"].content.as_deref(), Some("WAGMI"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn corrupt_custom_commands_are_not_reseeded() {
+    let (dir, store) = store("custom-corrupt");
+    let path = dir.join(format!("{}.json", customcommands::STORE_KEY));
+    std::fs::write(&path, "{ \"wagmi\": ").unwrap();
+
+    assert!(customcommands::load(&store).await.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{ \"wagmi\": ");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn missing_and_corrupt_entries_differ() {
+    let (dir, store) = store("corrupt");
+
+    let missing: Option<Vec<u64>> = store.load("numbers").await.unwrap();
+    assert_eq!(missing, None);
+
+    std::fs::write(dir.join("numbers.json"), "[1, 2,").unwrap();
+    let error = store.load::<Vec<u64>>("numbers").await.unwrap_err();
+    assert!(error.to_string().contains("'numbers' is corrupt"));
+    assert_eq!(
+        std::fs::read_to_string(dir.join("numbers.json")).unwrap(),
+        "[1, 2,"
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn typemap_values_are_persisted_under_their_key() {
+    let (dir, store) = store("persist");
+    let mut settings = Settings::new();
+    settings.insert(
+        1,
+        GuildSettings {
+            prefixes: vec!["?".to_string()],
+        },
+    );
+    let mut data = TypeMap::new();
+    data.insert::<SettingsMap>(Arc::new(RwLock::new(settings.clone())));
+    data.insert::<StoreMap>(Arc::new(store));
+    let data = RwLock::new(data);
+
+    persist::<SettingsMap, _>(&data, "settings").await.unwrap();
+
+    let store = Store::open(&dir).unwrap();
+    assert_eq!(store.load("settings").await.unwrap(), Some(settings));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn assets_outside_the_index_cannot_be_read() {
+    let images = images();
+
+    assert!(images.contains("panels.jpg"));
+    for name in ["zizan.png", "../Cargo.toml"] {
+        let error = images.read(name).await.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    }
+}
+
+#[test]
+fn missing_folder_is_an_empty_index() {
+    let assets = Assets::index("/definitely/not/here");
+
+    assert_eq!(assets.files().count(), 0);
+    assert_eq!(
+        assets.missing(["a.png", "a.png"]),
+        vec!["a.png".to_string()]
+    );
+}
+
+#[test]
+fn asset_listing_reports_missing_images() {
+    let content = assets::assets_response(&images(), &customcommands::defaults())
+        .content
+        .unwrap();
+
+    assert!(content.contains("`fraggy.jpg`"));
+    assert!(content.contains("`panels.jpg`"));
+    assert!(content.ends_with("Missing: `zizan.png`"));
+}
+
+#[test]
+fn reaction_roles_panel_reacts_with_every_role() {
+    let response = reactionroles::reactionroles_response();
+
+    assert_eq!(
+        response.embed.unwrap().title.as_deref(),
+        Some("Reaction Roles")
+    );
+    assert_eq!(response.reactions.len(), 5);
+}
@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+
+use ronnbot_rs::data::config::ProviderConfig;
+use ronnbot_rs::providers::{dexscreener::DexScreener, reservoir::Reservoir, MarketData};
+use ronnbot_rs::ronn_utils::apiclient::ApiClient;
+use serde_json::json;
+use serenity::model::prelude::interaction::application_command::{
+    CommandDataOption, CommandDataOptionValue,
+};
+use std::sync::Arc;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+pub const DEXSCREENER_SEARCH: &str = "/latest/dex/search";
+pub const RESERVOIR_COLLECTIONS: &str = "/collections/v6";
+pub const OPENSEA_COLLECTION: &str = "/api/v2/collections/boredapeyachtclub";
+pub const OPENSEA_STATS: &str = "/api/v2/collections/boredapeyachtclub/stats";
+
+pub fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!(
+        "{}/tests/fixtures/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    ))
+    .expect("Missing fixture")
+}
+
+/// Serves `fixture` with `status` on `route` for every request.
+pub async fn serve(server: &MockServer, route: &str, status: u16, fixture_name: &str) {
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(
+            ResponseTemplate::new(status)
+                .insert_header("content-type", "application/json")
+                .set_body_string(fixture(fixture_name)),
+        )
+        .mount(server)
+        .await;
+}
+
+/// A `MarketData` whose only providers point at `server`.
+pub fn market(server: &MockServer) -> MarketData {
+    MarketData::new(
+        Arc::new(ApiClient::default()),
+        vec![Arc::new(DexScreener::with_base_url(server.uri()))],
+        vec![Arc::new(Reservoir::with_base_url(
+            server.uri(),
+            "test-key".to_string(),
+        ))],
+        ProviderConfig::default(),
+    )
+}
+
+pub fn string_option(name: &str, value: &str) -> CommandDataOption {
+    let mut option: CommandDataOption =
+        serde_json::from_value(json!({ "name": name, "type": 3, "value": value }))
+            .expect("Valid option");
+    option.resolved = Some(CommandDataOptionValue::String(value.to_string()));
+    option
+}
+
+pub fn bool_option(name: &str, value: bool) -> CommandDataOption {
+    let mut option: CommandDataOption =
+        serde_json::from_value(json!({ "name": name, "type": 5, "value": value }))
+            .expect("Valid option");
+    option.resolved = Some(CommandDataOptionValue::Boolean(value));
+    option
+}
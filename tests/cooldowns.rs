@@ -0,0 +1,118 @@
+use ronnbot_rs::cooldowns::{Cooldowns, Scope};
+use ronnbot_rs::data::config::{Bucket, CooldownConfig, CooldownRule};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+fn rule(bucket: Bucket, seconds: u64) -> CooldownRule {
+    CooldownRule { bucket, seconds }
+}
+
+fn cooldowns() -> Cooldowns {
+    Cooldowns::new(CooldownConfig {
+        default: vec![rule(Bucket::User, 3)],
+        commands: HashMap::from([
+            (
+                "ilv".to_string(),
+                vec![rule(Bucket::User, 60), rule(Bucket::Channel, 30)],
+            ),
+            ("digest".to_string(), vec![rule(Bucket::Guild, 30)]),
+            ("ping".to_string(), vec![]),
+        ]),
+    })
+}
+
+fn scope(user_id: u64, channel_id: u64) -> Scope {
+    Scope {
+        user_id,
+        channel_id,
+        guild_id: Some(1),
+    }
+}
+
+#[test]
+fn user_cooldowns_expire() {
+    let mut cooldowns = cooldowns();
+    let now = Instant::now();
+
+    assert!(cooldowns.check("floor", scope(1, 10), now).is_ok());
+    let refused = cooldowns
+        .check("floor", scope(1, 10), now + Duration::from_secs(1))
+        .unwrap_err();
+    assert_eq!(refused.bucket, Bucket::User);
+    assert_eq!(
+        refused.to_string(),
+        "`floor` is on cooldown for you, try again in 2s"
+    );
+    assert!(cooldowns.check("floor", scope(2, 10), now).is_ok());
+    assert!(cooldowns
+        .check("floor", scope(1, 10), now + Duration::from_secs(3))
+        .is_ok());
+}
+
+#[test]
+fn channel_and_guild_buckets_are_shared() {
+    let mut cooldowns = cooldowns();
+    let now = Instant::now();
+
+    assert!(cooldowns.check("ilv", scope(1, 10), now).is_ok());
+    let refused = cooldowns.check("ILV", scope(2, 10), now).unwrap_err();
+    assert_eq!(refused.bucket, Bucket::Channel);
+    assert!(cooldowns.check("ilv", scope(2, 11), now).is_ok());
+
+    assert!(cooldowns.check("digest", scope(1, 10), now).is_ok());
+    let refused = cooldowns.check("digest", scope(3, 12), now).unwrap_err();
+    assert_eq!(
+        refused.to_string(),
+        "`digest` is on cooldown in this server, try again in 30s"
+    );
+}
+
+#[test]
+fn the_longest_running_cooldown_is_reported() {
+    let mut cooldowns = cooldowns();
+    let now = Instant::now();
+
+    cooldowns.check("ilv", scope(1, 10), now).unwrap();
+    let refused = cooldowns.check("ilv", scope(1, 10), now).unwrap_err();
+
+    assert_eq!(refused.bucket, Bucket::User);
+    assert_eq!(refused.remaining, Duration::from_secs(60));
+}
+
+#[test]
+fn spam_is_only_noticed_once() {
+    let mut cooldowns = cooldowns();
+    let now = Instant::now();
+
+    cooldowns.check("floor", scope(1, 10), now).unwrap();
+    assert!(
+        cooldowns
+            .check("floor", scope(1, 10), now)
+            .unwrap_err()
+            .notify
+    );
+    assert!(
+        !cooldowns
+            .check("floor", scope(1, 10), now)
+            .unwrap_err()
+            .notify
+    );
+
+    let later = now + Duration::from_secs(3);
+    cooldowns.check("floor", scope(1, 10), later).unwrap();
+    assert!(
+        cooldowns
+            .check("floor", scope(1, 10), later)
+            .unwrap_err()
+            .notify
+    );
+}
+
+#[test]
+fn an_empty_rule_list_turns_the_default_off() {
+    let mut cooldowns = cooldowns();
+    let now = Instant::now();
+
+    assert!(cooldowns.check("ping", scope(1, 10), now).is_ok());
+    assert!(cooldowns.check("ping", scope(1, 10), now).is_ok());
+}
@@ -0,0 +1,102 @@
+use ronnbot_rs::commands::steal::StealRequest;
+use ronnbot_rs::slashcommands::emoji::{
+    export_filename, find_emoji, info, list, slot_usage, zip_files,
+};
+use serenity::model::guild::{Emoji, PremiumTier};
+use std::io::{Cursor, Read};
+
+fn emoji(id: u64, name: &str, animated: bool) -> Emoji {
+    serde_json::from_value(serde_json::json!({
+        "id": id.to_string(),
+        "name": name,
+        "animated": animated,
+        "available": true,
+        "managed": false,
+        "require_colons": true,
+        "roles": [],
+    }))
+    .unwrap()
+}
+
+fn emojis() -> Vec<Emoji> {
+    vec![
+        emoji(1, "pepe", false),
+        emoji(2, "Dance", true),
+        emoji(3, "gib", false),
+    ]
+}
+
+#[test]
+fn emojis_are_found_by_mention_or_name() {
+    let emojis = emojis();
+
+    assert_eq!(find_emoji(&emojis, "<:whatever:3>").unwrap().name, "gib");
+    assert_eq!(find_emoji(&emojis, ":pepe:").unwrap().id.0, 1);
+    assert_eq!(find_emoji(&emojis, "dance").unwrap().id.0, 2);
+    assert!(find_emoji(&emojis, "<:pepe:99>").is_none());
+    assert!(find_emoji(&emojis, "nope").is_none());
+}
+
+#[test]
+fn list_shows_slot_usage_first() {
+    let emojis = emojis();
+
+    assert_eq!(
+        slot_usage(&emojis, PremiumTier::Tier1),
+        "Static 2/100, animated 1/100"
+    );
+    assert_eq!(
+        list(&emojis, PremiumTier::Tier0),
+        "Static 2/50, animated 1/50\n<a:Dance:2> <:gib:3> <:pepe:1>"
+    );
+}
+
+#[test]
+fn long_lists_stay_under_the_message_limit() {
+    let many: Vec<Emoji> = (1..=250)
+        .map(|id| emoji(id, &format!("emoji_number_{}", id), false))
+        .collect();
+
+    let reply = list(&many, PremiumTier::Tier3);
+
+    assert!(reply.len() <= 2000);
+    assert!(reply.ends_with("..."));
+}
+
+#[test]
+fn info_describes_the_emoji() {
+    let reply = info(&emoji(2, "Dance", true));
+
+    assert!(reply.starts_with("<a:Dance:2> `Dance`"));
+    assert!(reply.contains("Animated: yes"));
+    assert!(reply.ends_with("https://cdn.discordapp.com/emojis/2.gif"));
+}
+
+#[test]
+fn export_zips_every_emoji_with_unique_names() {
+    let first = emoji(1, "pepe", false);
+    let second = emoji(5, "pepe", false);
+    let name = export_filename(&first, &[]);
+    let clash = export_filename(&second, std::slice::from_ref(&name));
+    assert_eq!(name, "pepe.png");
+    assert_eq!(clash, "pepe-5.png");
+
+    let archive = zip_files(&[(name, b"one".to_vec()), (clash, b"two".to_vec())]).unwrap();
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+
+    assert_eq!(zip.len(), 2);
+    let mut contents = String::new();
+    zip.by_name("pepe-5.png")
+        .unwrap()
+        .read_to_string(&mut contents)
+        .unwrap();
+    assert_eq!(contents, "two");
+}
+
+#[test]
+fn every_custom_emoji_in_a_message_is_lifted_once() {
+    let request = StealRequest::from_text("lol <:pepe:1><a:dance:2> x < <:pepe:1> 3<4 <:gib:3>");
+
+    assert_eq!(request.names(), vec!["pepe", "dance", "gib"]);
+    assert!(request.failures.is_empty());
+}
@@ -0,0 +1,100 @@
+use chrono::{Duration, TimeZone, Utc};
+use ronnbot_rs::emojistats::{prune, record, report, total, EmojiStats, RETENTION_DAYS};
+use ronnbot_rs::ronn_utils::utils::custom_emojis;
+use serenity::model::guild::Emoji;
+use std::collections::HashSet;
+
+const GUILD: u64 = 1;
+
+fn known(ids: &[u64]) -> HashSet<u64> {
+    ids.iter().copied().collect()
+}
+
+fn emoji(id: u64, name: &str) -> Emoji {
+    serde_json::from_value(serde_json::json!({
+        "id": id.to_string(),
+        "name": name,
+        "animated": false,
+        "available": true,
+        "managed": false,
+        "require_colons": true,
+        "roles": [],
+    }))
+    .unwrap()
+}
+
+#[test]
+fn message_emojis_count_once_per_message() {
+    let ids: Vec<u64> = custom_emojis("<:pepe:1> <:pepe:1> <a:dance:2> <notanemoji>")
+        .into_iter()
+        .map(|emoji| emoji.id.0)
+        .collect();
+
+    assert_eq!(ids, vec![1, 2]);
+}
+
+#[test]
+fn counts_are_kept_per_day_and_window() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+    let mut stats = EmojiStats::default();
+    let emojis = known(&[1, 2]);
+
+    record(&mut stats, GUILD, &emojis, [1], now - Duration::days(10));
+    record(&mut stats, GUILD, &emojis, [1, 2], now);
+    record(&mut stats, GUILD, &emojis, [1], now);
+
+    let guild = stats.get(&GUILD);
+    assert_eq!(total(guild, 1, None, now), 3);
+    assert_eq!(total(guild, 1, Some(7), now), 2);
+    assert_eq!(total(guild, 2, Some(1), now), 1);
+    assert_eq!(total(stats.get(&2), 1, None, now), 0);
+}
+
+#[test]
+fn emojis_from_other_guilds_are_not_counted() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+    let mut stats = EmojiStats::default();
+
+    record(&mut stats, GUILD, &known(&[1]), [1, 7, 8], now);
+    record(&mut stats, 2, &known(&[]), [1], now);
+
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[&GUILD].keys().collect::<Vec<_>>(), [&1]);
+}
+
+#[test]
+fn pruning_drops_old_days_and_empty_emojis() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+    let mut stats = EmojiStats::default();
+    let emojis = known(&[1, 2]);
+    let old = now - Duration::days(RETENTION_DAYS + 5);
+
+    record(&mut stats, GUILD, &emojis, [1, 2], old);
+    record(&mut stats, GUILD, &emojis, [1], now);
+    record(&mut stats, 2, &emojis, [1], old);
+    prune(&mut stats, now);
+
+    assert_eq!(stats[&GUILD][&1].len(), 1);
+    assert!(!stats[&GUILD].contains_key(&2));
+    assert!(!stats.contains_key(&2));
+}
+
+#[test]
+fn report_ranks_and_lists_unused_emojis() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+    let mut stats = EmojiStats::default();
+    let known = known(&[1, 2, 3, 99]);
+    record(&mut stats, GUILD, &known, [1, 2], now);
+    record(&mut stats, GUILD, &known, [2], now);
+    // Used in the guild but since deleted, never shown
+    record(&mut stats, GUILD, &known, [99], now);
+    let emojis = vec![emoji(1, "pepe"), emoji(2, "gib"), emoji(3, "dust")];
+
+    let reply = report(&emojis, stats.get(&GUILD), Some(30), now);
+
+    assert_eq!(
+        reply,
+        "Emoji use in the last 30 days\nMost used: <:gib:2> 2, <:pepe:1> 1\nNot used (1): <:dust:3>"
+    );
+    assert!(report(&emojis, None, None, now).contains("No emoji use recorded yet"));
+}
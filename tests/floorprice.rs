@@ -0,0 +1,216 @@
+mod common;
+
+use common::{
+    bool_option, market, serve, string_option, OPENSEA_COLLECTION, OPENSEA_STATS,
+    RESERVOIR_COLLECTIONS,
+};
+use ronnbot_rs::data::config::ProviderConfig;
+use ronnbot_rs::providers::{opensea::OpenSea, reservoir::Reservoir, MarketData};
+use ronnbot_rs::ronn_utils::apiclient::ApiClient;
+use ronnbot_rs::slashcommands::floorprice;
+use std::sync::Arc;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn renders_the_first_collection() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(RESERVOIR_COLLECTIONS))
+        .and(query_param("name", "bored ape"))
+        .and(header("x-api-key", "test-key"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(common::fixture("reservoir_collections.json")),
+        )
+        .mount(&server)
+        .await;
+
+    let output = floorprice::run(
+        &market(&server),
+        None,
+        &[string_option("project", "bored ape")],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        output,
+        "The floor price for [Bored Ape Yacht Club] is [29.5]ETH and is on [opensea.io]\n_Live data_"
+    );
+}
+
+#[tokio::test]
+async fn verbose_lists_every_collection() {
+    let server = MockServer::start().await;
+    serve(
+        &server,
+        RESERVOIR_COLLECTIONS,
+        200,
+        "reservoir_collections.json",
+    )
+    .await;
+
+    let output = floorprice::run(
+        &market(&server),
+        None,
+        &[
+            string_option("project", "ape"),
+            bool_option("verbose", true),
+        ],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        output,
+        "The floor price for [Bored Ape Yacht Club] is [29.5]ETH and is on [opensea.io]\n\
+         The floor price for [Mutant Ape Yacht Club] is [5.1]ETH and is on [blur.io]\n\
+         \n_Live data_"
+    );
+}
+
+#[tokio::test]
+async fn empty_results_say_so() {
+    let server = MockServer::start().await;
+    serve(&server, RESERVOIR_COLLECTIONS, 200, "reservoir_empty.json").await;
+
+    let output = floorprice::run(
+        &market(&server),
+        None,
+        &[string_option("project", "nothing")],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(output, "There is no collection found for the name nothing ");
+}
+
+#[tokio::test]
+async fn not_found_is_reported() {
+    let server = MockServer::start().await;
+    serve(&server, RESERVOIR_COLLECTIONS, 404, "reservoir_empty.json").await;
+
+    let output = floorprice::run(&market(&server), None, &[string_option("project", "ape")])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        output,
+        "Something went wrong contact izee: Nothing was found"
+    );
+}
+
+#[tokio::test]
+async fn rate_limits_are_reported() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(RESERVOIR_COLLECTIONS))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "60"))
+        .mount(&server)
+        .await;
+
+    let output = floorprice::run(&market(&server), None, &[string_option("project", "ape")])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        output,
+        "Something went wrong contact izee: API is rate limited, try again in 60s"
+    );
+}
+
+#[tokio::test]
+async fn malformed_json_is_reported() {
+    let server = MockServer::start().await;
+    serve(&server, RESERVOIR_COLLECTIONS, 200, "malformed.json").await;
+
+    let output = floorprice::run(&market(&server), None, &[string_option("project", "ape")])
+        .await
+        .unwrap_err();
+
+    assert!(output.starts_with("Something went wrong contact izee: API answer could not be parsed"));
+}
+
+#[tokio::test]
+async fn reports_a_missing_api_key() {
+    let market = MarketData::new(
+        Arc::new(ApiClient::default()),
+        vec![],
+        vec![],
+        ProviderConfig::default(),
+    );
+
+    let output = floorprice::run(&market, None, &[string_option("project", "ape")])
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        output,
+        "NFT floor price lookup is not configured on this bot"
+    );
+}
+
+#[tokio::test]
+async fn falls_back_to_opensea() {
+    let server = MockServer::start().await;
+    serve(&server, RESERVOIR_COLLECTIONS, 401, "reservoir_empty.json").await;
+    Mock::given(method("GET"))
+        .and(path(OPENSEA_COLLECTION))
+        .and(header("x-api-key", "opensea-key"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(common::fixture("opensea_collection.json")),
+        )
+        .mount(&server)
+        .await;
+    serve(&server, OPENSEA_STATS, 200, "opensea_stats.json").await;
+    let market = MarketData::new(
+        Arc::new(ApiClient::default()),
+        vec![],
+        vec![
+            Arc::new(Reservoir::with_base_url(
+                server.uri(),
+                "test-key".to_string(),
+            )),
+            Arc::new(OpenSea::with_base_url(
+                format!("{}/api/v2", server.uri()),
+                "opensea-key".to_string(),
+            )),
+        ],
+        ProviderConfig::default(),
+    );
+
+    let output = floorprice::run(
+        &market,
+        None,
+        &[string_option("project", "Bored Ape Yacht Club")],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        output,
+        "The floor price for [Bored Ape Yacht Club] is [29.4]ETH and is on [opensea.io]\n_Live data_"
+    );
+}
+
+#[test]
+fn opensea_slugs_drop_spaces_and_case() {
+    assert_eq!(
+        ronnbot_rs::providers::opensea::slug("Bored Ape Yacht Club"),
+        "boredapeyachtclub"
+    );
+    assert_eq!(
+        ronnbot_rs::providers::opensea::slug("pudgy-penguins"),
+        "pudgy-penguins"
+    );
+}
+
+#[tokio::test]
+async fn missing_options_are_an_error() {
+    let server = MockServer::start().await;
+
+    let output = floorprice::run(&market(&server), None, &[bool_option("verbose", true)]).await;
+
+    assert_eq!(output.unwrap_err(), "Please Provide a collection name");
+}
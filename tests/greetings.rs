@@ -0,0 +1,132 @@
+use chrono::{Duration, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ronnbot_rs::customcommands::template::render;
+use ronnbot_rs::data::config::Config;
+use ronnbot_rs::greetings::{self, PendingGrant, PendingRoles};
+use serenity::http::error::ErrorResponse;
+use serenity::http::{HttpError, StatusCode};
+use serenity::prelude::SerenityError;
+
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(1)
+}
+
+#[test]
+fn greeting_placeholders() {
+    let context = greetings::context(42, "ronn", 7, Some("Ronn Club".to_string()), Some(120));
+
+    assert_eq!(
+        render(
+            "Welcome {user} ({name}) to {server}, member #{members}! Say hi in {channel}",
+            &context,
+            &mut rng()
+        ),
+        "Welcome <@42> (ronn) to Ronn Club, member #120! Say hi in <#7>"
+    );
+}
+
+#[test]
+fn unknown_guild_details_stay_as_written() {
+    let context = greetings::context(42, "ronn", 7, None, None);
+
+    assert_eq!(
+        render("{name} joined {server} ({members})", &context, &mut rng()),
+        "ronn joined {server} ({members})"
+    );
+}
+
+#[test]
+fn greetings_are_read_per_guild() {
+    let config: Config = serde_json::from_str(
+        r#"{
+            "channel_id": 1, "emotes": [], "role_ids": [],
+            "greetings": {
+                "10": {
+                    "channel_id": 20,
+                    "welcome": { "embed": { "title": "Welcome {name}", "image": "welcome.png" } },
+                    "auto_roles": [30, 31],
+                    "role_delay_seconds": 600
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let greeting = &config.greetings[&10];
+    assert_eq!(greeting.channel_id, Some(20));
+    assert!(greeting.goodbye.is_none());
+    assert_eq!(greeting.auto_roles, [30, 31]);
+    assert_eq!(greeting.role_delay_seconds, 600);
+
+    let context = greetings::context(42, "ronn", 20, None, None);
+    let (response, files) = greeting
+        .welcome
+        .as_ref()
+        .unwrap()
+        .render(&context, &mut rng());
+    let embed = response.embed.unwrap();
+    assert_eq!(embed.title.as_deref(), Some("Welcome ronn"));
+    assert_eq!(embed.image.as_deref(), Some("attachment://welcome.png"));
+    assert_eq!(files, ["welcome.png"]);
+}
+
+fn grant(user_id: u64, due_in: i64) -> PendingGrant {
+    PendingGrant {
+        guild_id: 1,
+        user_id,
+        role_ids: vec![10, 20],
+        due: Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap() + Duration::seconds(due_in),
+    }
+}
+
+#[test]
+fn delayed_roles_wait_until_due() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+    let mut pending = PendingRoles::new();
+    greetings::queue(&mut pending, grant(1, 0));
+    greetings::queue(&mut pending, grant(2, 600));
+    // Rejoining replaces the earlier grant
+    greetings::queue(&mut pending, grant(1, -5));
+
+    assert_eq!(greetings::take_due(&mut pending, now), vec![grant(1, -5)]);
+    assert_eq!(pending, vec![grant(2, 600)]);
+    assert!(greetings::take_due(&mut pending, now).is_empty());
+
+    let saved = serde_json::to_string(&pending).unwrap();
+    let loaded: PendingRoles = serde_json::from_str(&saved).unwrap();
+    assert_eq!(
+        greetings::take_due(&mut pending.clone(), now + Duration::seconds(600)),
+        loaded
+    );
+}
+
+fn http_error(status: u16, code: isize) -> SerenityError {
+    let error = serde_json::from_value(serde_json::json!({ "code": code, "message": "" })).unwrap();
+    SerenityError::Http(Box::new(HttpError::UnsuccessfulRequest(ErrorResponse {
+        status_code: StatusCode::from_u16(status).unwrap(),
+        url: "https://discord.com/api/v10/guilds/1/members/1"
+            .parse()
+            .unwrap(),
+        error,
+    })))
+}
+
+#[test]
+fn failed_grants_are_retried_unless_the_member_left() {
+    assert!(greetings::member_gone(&http_error(404, 10007)));
+    assert!(!greetings::member_gone(&http_error(404, 10011)));
+    assert!(!greetings::member_gone(&http_error(429, 0)));
+    assert!(!greetings::member_gone(&http_error(502, 0)));
+
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+    let mut pending = PendingRoles::new();
+    greetings::retry(&mut pending, grant(1, -5), now);
+    assert_eq!(pending, vec![grant(1, greetings::RETRY_SECONDS)]);
+    assert!(greetings::take_due(&mut pending, now).is_empty());
+
+    // A newer grant from joining again wins over the retry
+    let mut pending = vec![grant(2, 600)];
+    greetings::retry(&mut pending, grant(2, -5), now);
+    assert_eq!(pending, vec![grant(2, 600)]);
+}
@@ -0,0 +1,175 @@
+use ronnbot_rs::access::{Caller, GuildAccess};
+use ronnbot_rs::commands::assets::*;
+use ronnbot_rs::commands::help::{
+    custom_entry, details, page, page_count, parse_button, prefix_entries, response, slash_entries,
+    HelpEntry, Viewer, PER_PAGE,
+};
+use ronnbot_rs::commands::meta::*;
+use ronnbot_rs::commands::steal::*;
+use ronnbot_rs::customcommands::{CustomCommand, CustomCommands};
+use ronnbot_rs::slashcommands::access::set_disabled;
+use ronnbot_rs::slashcommands::registry;
+use serenity::framework::standard::macros::group;
+use serenity::model::Permissions;
+
+#[group]
+#[commands(ping, steal, assets)]
+struct General;
+
+const GUILD: u64 = 1;
+
+fn member(permissions: Permissions) -> Viewer {
+    Viewer {
+        guild_id: Some(GUILD),
+        channel_id: 10,
+        caller: Some(Caller {
+            roles: vec![],
+            permissions,
+        }),
+        ..Default::default()
+    }
+}
+
+fn names(entries: &[HelpEntry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+fn entry(n: usize) -> HelpEntry {
+    HelpEntry {
+        name: format!("!command{}", n),
+        command: format!("command{}", n),
+        description: "Does things".to_string(),
+        usage: None,
+        examples: vec![],
+    }
+}
+
+#[test]
+fn prefix_commands_are_filtered_by_permissions() {
+    let viewer = member(Permissions::SEND_MESSAGES);
+    assert_eq!(
+        names(&prefix_entries(&[&GENERAL_GROUP], "?", &viewer)),
+        vec!["?ping"]
+    );
+
+    let owner = Viewer {
+        is_owner: true,
+        ..member(Permissions::MANAGE_EMOJIS_AND_STICKERS)
+    };
+    let entries = prefix_entries(&[&GENERAL_GROUP], "!", &owner);
+    assert_eq!(names(&entries), vec!["!ping", "!steal", "!assets"]);
+    assert_eq!(
+        entries[1].examples,
+        vec!["!steal <:pepe:123456789012345678> name=frog".to_string()]
+    );
+    assert!(entries[1].usage.as_deref().unwrap().starts_with("!steal <"));
+}
+
+#[test]
+fn direct_messages_hide_server_commands() {
+    let viewer = Viewer {
+        channel_id: 10,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        names(&prefix_entries(&[&GENERAL_GROUP], "!", &viewer)),
+        vec!["!ping"]
+    );
+    let slash = slash_entries(&registry(), &viewer);
+    assert!(names(&slash).contains(&"/help"));
+    assert!(!names(&slash).contains(&"/emoji"));
+}
+
+#[test]
+fn slash_commands_follow_default_permissions_and_rules() {
+    let mut access = GuildAccess::default();
+    set_disabled(&mut access, "coin", true);
+    let viewer = Viewer {
+        access: Some(access),
+        ..member(Permissions::SEND_MESSAGES)
+    };
+    let entries = slash_entries(&registry(), &viewer);
+    let shown = names(&entries);
+
+    assert!(shown.contains(&"/help"));
+    assert!(shown.contains(&"/floorprice"));
+    assert!(!shown.contains(&"/coin"));
+    assert!(!shown.contains(&"/emoji"));
+    assert!(!shown.contains(&"/access"));
+
+    let admin = slash_entries(&registry(), &member(Permissions::ADMINISTRATOR));
+    let emoji = admin.iter().find(|entry| entry.name == "/emoji").unwrap();
+    assert_eq!(
+        emoji.usage.as_deref(),
+        Some("/emoji rename|delete|info|list|stats|export")
+    );
+    assert!(names(&admin).contains(&"Apps > Steal emojis"));
+}
+
+#[test]
+fn custom_replies_only_show_this_guilds_tags() {
+    let mut commands = CustomCommands::new();
+    commands.insert("ilv".to_string(), CustomCommand::default());
+    commands.insert(format!("{}:hello", GUILD), CustomCommand::default());
+    commands.insert("2:secret".to_string(), CustomCommand::default());
+
+    let entry = custom_entry(&commands, "!", &member(Permissions::empty())).unwrap();
+    assert_eq!(entry.description, "`!hello` `!ilv`");
+
+    let mut access = GuildAccess::default();
+    set_disabled(&mut access, "hello", true);
+    set_disabled(&mut access, "ilv", true);
+    let viewer = Viewer {
+        access: Some(access),
+        ..member(Permissions::empty())
+    };
+    assert_eq!(custom_entry(&commands, "!", &viewer), None);
+}
+
+#[test]
+fn pages_have_buttons_for_the_asker() {
+    let entries: Vec<HelpEntry> = (0..PER_PAGE * 2 + 1).map(entry).collect();
+    assert_eq!(page_count(&entries), 3);
+
+    let first = page(&entries, 0, 42);
+    assert_eq!(first.embed.as_ref().unwrap().fields.len(), PER_PAGE);
+    assert!(first.buttons[0].disabled);
+    assert!(!first.buttons[1].disabled);
+    assert_eq!(parse_button(&first.buttons[1].custom_id), Some((42, 1)));
+
+    let last = page(&entries, 9, 42);
+    assert_eq!(last.embed.as_ref().unwrap().fields.len(), 1);
+    assert!(last.buttons[1].disabled);
+    assert!(last
+        .embed
+        .unwrap()
+        .footer
+        .unwrap()
+        .starts_with("Page 3 of 3"));
+
+    assert!(page(&entries[..1], 0, 42).buttons.is_empty());
+    assert_eq!(parse_button("other:42:1"), None);
+}
+
+#[test]
+fn details_are_found_with_or_without_prefix() {
+    let owner = Viewer {
+        is_owner: true,
+        ..member(Permissions::ADMINISTRATOR)
+    };
+    let mut entries = prefix_entries(&[&GENERAL_GROUP], "r!", &owner);
+    entries.extend(slash_entries(&registry(), &owner));
+
+    for query in ["steal", "r!steal", "!STEAL"] {
+        let embed = details(&entries, query).unwrap().embed.unwrap();
+        assert_eq!(embed.title.as_deref(), Some("r!steal"));
+        assert_eq!(embed.fields[1].0, "Examples");
+    }
+    assert!(details(&entries, "/emoji").is_some());
+    assert!(details(&entries, "steal emojis").is_some());
+    assert_eq!(
+        response(&entries, "nope", 42).content.as_deref(),
+        Some("There is no command called `nope` that you can use")
+    );
+}
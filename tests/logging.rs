@@ -0,0 +1,23 @@
+use ronnbot_rs::ronn_utils::logging::{filter, LogFormat, DEFAULT_LOG_LEVEL};
+use tracing_subscriber::EnvFilter;
+
+#[test]
+fn formats_default_to_text() {
+    assert_eq!(LogFormat::parse("JSON"), LogFormat::Json);
+    assert_eq!(LogFormat::parse(" json "), LogFormat::Json);
+    assert_eq!(LogFormat::parse("pretty"), LogFormat::Text);
+    assert_eq!(LogFormat::default(), LogFormat::Text);
+}
+
+#[test]
+fn bad_levels_fall_back_to_the_default() {
+    let default = EnvFilter::new(DEFAULT_LOG_LEVEL).to_string();
+
+    assert_eq!(
+        filter(Some("warn,ronnbot_rs=debug")).to_string(),
+        EnvFilter::new("warn,ronnbot_rs=debug").to_string()
+    );
+    assert_ne!(filter(Some("warn")).to_string(), default);
+    assert_eq!(filter(Some("ronnbot_rs=[")).to_string(), default);
+    assert_eq!(filter(None).to_string(), default);
+}
@@ -0,0 +1,94 @@
+use hyper::StatusCode;
+use ronnbot_rs::metrics::server::{Health, ShardHealth};
+use ronnbot_rs::metrics::{escape, Registry};
+use ronnbot_rs::ronn_utils::apiclient::ApiClient;
+
+fn shard(id: u64, stage: &str, latency_ms: Option<u64>) -> ShardHealth {
+    ShardHealth {
+        id,
+        stage: stage.to_string(),
+        latency_ms,
+    }
+}
+
+#[test]
+fn counters_add_up_per_label_set() {
+    let registry = Registry::new();
+    let ok = [("kind", "slash"), ("command", "coin"), ("outcome", "ok")];
+    registry.increment("ronnbot_commands_total", &ok);
+    registry.increment("ronnbot_commands_total", &ok);
+    registry.increment(
+        "ronnbot_commands_total",
+        &[("kind", "slash"), ("command", "coin"), ("outcome", "error")],
+    );
+
+    assert_eq!(registry.get("ronnbot_commands_total", &ok), 2.0);
+    assert!(registry
+        .render()
+        .contains("ronnbot_commands_total{kind=\"slash\",command=\"coin\",outcome=\"ok\"} 2\n"));
+}
+
+#[test]
+fn summaries_render_sum_and_count() {
+    let registry = Registry::new();
+    registry.observe(
+        "ronnbot_api_request_seconds",
+        &[("host", "api.dexscreener.com")],
+        0.25,
+    );
+    registry.observe(
+        "ronnbot_api_request_seconds",
+        &[("host", "api.dexscreener.com")],
+        0.5,
+    );
+
+    let text = registry.render();
+    assert!(text.contains("# TYPE ronnbot_api_request_seconds summary\n"));
+    assert!(text.contains("ronnbot_api_request_seconds_sum{host=\"api.dexscreener.com\"} 0.75\n"));
+    assert!(text.contains("ronnbot_api_request_seconds_count{host=\"api.dexscreener.com\"} 2\n"));
+}
+
+#[test]
+fn unknown_hosts_share_one_label() {
+    assert_eq!(
+        ApiClient::host("https://api.dexscreener.com/latest/dex/search?q=eth"),
+        "api.dexscreener.com"
+    );
+    assert_eq!(ApiClient::host("https://example.com/emoji.png"), "other");
+    assert_eq!(ApiClient::host("not a url"), "other");
+}
+
+#[test]
+fn label_values_are_escaped() {
+    assert_eq!(escape("say \"hi\"\\\n"), "say \\\"hi\\\"\\\\\\n");
+}
+
+#[test]
+fn healthy_only_when_every_shard_is_connected() {
+    assert!(!Health::default().is_healthy());
+    assert_eq!(Health::default().status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let connected = Health {
+        shards: vec![shard(0, "connected", Some(42)), shard(1, "connected", None)],
+    };
+    assert!(connected.is_healthy());
+    assert_eq!(connected.status(), StatusCode::OK);
+
+    let resuming = Health {
+        shards: vec![shard(0, "connected", Some(42)), shard(1, "resuming", None)],
+    };
+    assert!(!resuming.is_healthy());
+}
+
+#[test]
+fn shard_gauges_skip_unknown_latency() {
+    let health = Health {
+        shards: vec![shard(0, "connected", Some(42)), shard(1, "resuming", None)],
+    };
+    let text = health.render();
+
+    assert!(text.contains("ronnbot_shard_connected{shard=\"0\"} 1\n"));
+    assert!(text.contains("ronnbot_shard_connected{shard=\"1\"} 0\n"));
+    assert!(text.contains("ronnbot_gateway_latency_seconds{shard=\"0\"} 0.042\n"));
+    assert!(!text.contains("ronnbot_gateway_latency_seconds{shard=\"1\"}"));
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc, Weekday};
+use ronnbot_rs::scheduler::{parse_schedule, DigestSchedule};
+
+/// A Friday.
+fn friday() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap()
+}
+
+fn schedule(cron: &str, last_run: Option<DateTime<Utc>>) -> DigestSchedule {
+    DigestSchedule {
+        cron: cron.to_string(),
+        last_run,
+        ..Default::default()
+    }
+}
+
+fn weekdays(cron: &str) -> Vec<Weekday> {
+    parse_schedule(cron)
+        .unwrap()
+        .after(&friday())
+        .take(7)
+        .map(|run| run.weekday())
+        .collect()
+}
+
+#[test]
+fn daily_and_hourly_are_due_once_per_period() {
+    let daily = schedule("daily", Some(friday()));
+    assert!(!daily.is_due(friday() + Duration::hours(11)));
+    assert!(daily.is_due(friday() + Duration::hours(12)));
+
+    let hourly = schedule(" Hourly ", Some(friday()));
+    assert!(!hourly.is_due(friday() + Duration::minutes(59)));
+    assert!(hourly.is_due(friday() + Duration::hours(1)));
+}
+
+#[test]
+fn five_field_weekdays_count_from_sunday_as_zero() {
+    use Weekday::*;
+
+    assert_eq!(weekdays("0 9 * * 1-5"), [Mon, Tue, Wed, Thu, Fri, Mon, Tue]);
+    assert_eq!(weekdays("0 9 * * 0"), [Sun; 7]);
+    assert_eq!(weekdays("0 9 * * 7"), [Sun; 7]);
+    assert_eq!(weekdays("0 9 * * 5-7"), [Sat, Sun, Fri, Sat, Sun, Fri, Sat]);
+    assert_eq!(
+        weekdays("0 9 * * sat,sun"),
+        [Sat, Sun, Sat, Sun, Sat, Sun, Sat]
+    );
+    assert_eq!(weekdays("0 9 * * */3"), [Sat, Sun, Wed, Sat, Sun, Wed, Sat]);
+
+    let first = parse_schedule("30 9 * * 1-5")
+        .unwrap()
+        .after(&friday())
+        .next()
+        .unwrap();
+    assert_eq!((first.day(), first.hour(), first.minute()), (13, 9, 30));
+}
+
+#[test]
+fn invalid_schedules_are_refused() {
+    for cron in [
+        "whenever",
+        "0 9 * * 8",
+        "0 9 * * 5-1",
+        "0 9 * * mon/0",
+        "61 * * * *",
+    ] {
+        assert!(parse_schedule(cron).is_err(), "{} should not parse", cron);
+    }
+    assert!(!schedule("whenever", Some(friday())).is_due(friday() + Duration::days(1)));
+}
+
+#[test]
+fn missed_runs_are_caught_up_once() {
+    let mut digest = schedule("0 9 * * *", Some(friday() - Duration::days(3)));
+    assert!(digest.is_due(friday()));
+
+    digest.last_run = Some(friday());
+    assert!(!digest.is_due(friday() + Duration::hours(1)));
+    assert!(digest.is_due(friday() + Duration::hours(21)));
+
+    assert!(!schedule("0 9 * * *", None).is_due(friday()));
+}
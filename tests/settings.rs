@@ -0,0 +1,57 @@
+use ronnbot_rs::settings::{
+    matching_prefix, parse_prefixes, prefixes, GuildSettings, DEFAULT_PREFIX, MAX_PREFIXES,
+};
+use ronnbot_rs::slashcommands::settings::{describe_prefixes, set_prefixes};
+
+#[test]
+fn guilds_without_prefixes_use_the_default() {
+    assert_eq!(prefixes(None), vec![DEFAULT_PREFIX.to_string()]);
+    assert_eq!(
+        prefixes(Some(&GuildSettings::default())),
+        vec![DEFAULT_PREFIX.to_string()]
+    );
+    assert_eq!(
+        describe_prefixes(None),
+        "Commands work with `!` or by mentioning me"
+    );
+}
+
+#[test]
+fn the_longest_matching_prefix_wins() {
+    let prefixes = vec!["?".to_string(), "??".to_string(), "r!".to_string()];
+
+    assert_eq!(matching_prefix(&prefixes, "??ilv"), Some("??".to_string()));
+    assert_eq!(
+        matching_prefix(&prefixes, "r!floor"),
+        Some("r!".to_string())
+    );
+    assert_eq!(matching_prefix(&prefixes, "!ilv"), None);
+}
+
+#[test]
+fn prefixes_are_validated() {
+    assert_eq!(
+        parse_prefixes(" ? r! ? "),
+        Ok(vec!["?".to_string(), "r!".to_string()])
+    );
+    assert!(parse_prefixes("   ").is_err());
+    assert!(parse_prefixes("toolong").is_err());
+    assert!(parse_prefixes("/").is_err());
+    let many = (0..=MAX_PREFIXES)
+        .map(|n| format!("{}!", n))
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert!(parse_prefixes(&many).is_err());
+}
+
+#[test]
+fn invalid_prefixes_keep_the_old_ones() {
+    let mut settings = GuildSettings::default();
+
+    assert_eq!(
+        set_prefixes(&mut settings, "? $"),
+        "Commands work with `?`, `$` or by mentioning me"
+    );
+    assert!(set_prefixes(&mut settings, "").starts_with("Please"));
+    assert_eq!(settings.prefixes, vec!["?".to_string(), "$".to_string()]);
+}
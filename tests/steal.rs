@@ -0,0 +1,203 @@
+use ronnbot_rs::commands::steal::{
+    emoji_name, image_kind, slot_limit, summary, StealRequest, StealSource, MAX_EMOJI_BYTES,
+};
+use ronnbot_rs::ronn_utils::apiclient::{check_download_url, is_public, ApiClient, ApiError};
+use serenity::model::channel::Attachment;
+use serenity::model::guild::PremiumTier;
+use wiremock::matchers::path;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn attachment(filename: &str, content_type: &str) -> Attachment {
+    serde_json::from_value(serde_json::json!({
+        "id": "1",
+        "filename": filename,
+        "size": 10,
+        "url": format!("https://cdn.example/{}", filename),
+        "proxy_url": format!("https://proxy.example/{}", filename),
+        "content_type": content_type,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn arguments_become_sources_or_failures() {
+    let request = StealRequest::parse(
+        "<:pepe:123> <a:dance:456> https://x.example/img/cool-cat.png?size=64 hello",
+    );
+
+    assert_eq!(
+        request.sources,
+        vec![
+            StealSource {
+                name: "pepe".to_string(),
+                url: "https://cdn.discordapp.com/emojis/123.png".to_string(),
+            },
+            StealSource {
+                name: "dance".to_string(),
+                url: "https://cdn.discordapp.com/emojis/456.gif".to_string(),
+            },
+            StealSource {
+                name: "cool-cat".to_string(),
+                url: "https://x.example/img/cool-cat.png?size=64".to_string(),
+            },
+        ]
+    );
+    assert_eq!(request.failures.len(), 1);
+    assert_eq!(request.failures[0].0, "hello");
+    assert_eq!(request.names(), vec!["pepe", "dance", "cool_cat"]);
+}
+
+#[test]
+fn rename_applies_to_every_source() {
+    let request = StealRequest::parse("name=frog <:pepe:1> <:pepe2:2>");
+
+    assert_eq!(request.names(), vec!["frog", "frog_2"]);
+}
+
+#[test]
+fn plain_http_urls_are_refused() {
+    let request = StealRequest::parse("http://x.example/cat.png");
+
+    assert!(request.sources.is_empty());
+    assert_eq!(
+        request.failures,
+        vec![(
+            "http://x.example/cat.png".to_string(),
+            "only https image URLs can be used".to_string()
+        )]
+    );
+}
+
+#[test]
+fn only_public_addresses_can_be_downloaded() {
+    for ip in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "::1",
+        "fe80::1",
+        "fd00::1",
+        "::ffff:127.0.0.1",
+    ] {
+        assert!(!is_public(ip.parse().unwrap()), "{} is not public", ip);
+    }
+    for ip in ["1.1.1.1", "162.159.130.233", "2606:4700::6810:84e5"] {
+        assert!(is_public(ip.parse().unwrap()), "{} is public", ip);
+    }
+}
+
+#[test]
+fn download_urls_must_be_https_and_public() {
+    let check = |url: &str| check_download_url(&url.parse().unwrap());
+
+    assert!(check("https://cdn.discordapp.com/emojis/1.png").is_ok());
+    assert!(check("https://1.1.1.1/a.png").is_ok());
+    assert_eq!(
+        check("http://cdn.discordapp.com/emojis/1.png"),
+        Err(ApiError::Blocked("only https URLs can be used".to_string()))
+    );
+    assert_eq!(
+        check("https://169.254.169.254/latest/meta-data"),
+        Err(ApiError::Blocked(
+            "169.254.169.254 is not a public address".to_string()
+        ))
+    );
+    assert!(check("https://[::1]/a.png").is_err());
+}
+
+#[tokio::test]
+async fn downloads_refuse_private_hosts() {
+    let client = ApiClient::default();
+
+    let error = client
+        .download("https://localhost/a.png", MAX_EMOJI_BYTES)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, ApiError::Blocked(_)), "{:?}", error);
+}
+
+#[test]
+fn only_image_attachments_are_used() {
+    let mut request = StealRequest::default();
+
+    request.add_attachments(&[
+        attachment("smile.png", "image/png"),
+        attachment("notes.txt", "text/plain"),
+    ]);
+
+    assert_eq!(request.names(), vec!["smile"]);
+    assert_eq!(request.failures[0].0, "notes.txt");
+}
+
+#[test]
+fn names_are_made_valid() {
+    assert_eq!(emoji_name("a"), "emojia");
+    assert_eq!(emoji_name("hi there!"), "hi_there_");
+    assert_eq!(emoji_name(&"x".repeat(40)).len(), 32);
+}
+
+#[test]
+fn image_kind_follows_the_magic_bytes() {
+    assert!(!image_kind(b"\x89PNG\r\n").unwrap().animated);
+    assert_eq!(image_kind(b"\xFF\xD8\xFF\xE0").unwrap().mime, "image/jpeg");
+    assert!(image_kind(b"GIF89a").unwrap().animated);
+    assert_eq!(image_kind(b"<html>"), None);
+}
+
+#[test]
+fn boosts_add_slots() {
+    assert_eq!(slot_limit(PremiumTier::Tier0), 50);
+    assert_eq!(slot_limit(PremiumTier::Tier3), 250);
+}
+
+#[test]
+fn one_summary_for_the_batch() {
+    let content = summary(
+        &["<:pepe:1>".to_string()],
+        &[("big".to_string(), "File is larger than 256 KB".to_string())],
+    )
+    .content
+    .unwrap();
+
+    assert_eq!(
+        content,
+        "Added <:pepe:1>\nCould not add `big`: File is larger than 256 KB"
+    );
+    assert!(summary(&[], &[])
+        .content
+        .unwrap()
+        .starts_with("Please supply"));
+}
+
+#[tokio::test]
+async fn downloads_stop_at_the_size_limit() {
+    let server = MockServer::start().await;
+    Mock::given(path("/small.png"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"\x89PNG".to_vec()))
+        .mount(&server)
+        .await;
+    Mock::given(path("/big.png"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_bytes(vec![0; MAX_EMOJI_BYTES as usize + 1]),
+        )
+        .mount(&server)
+        .await;
+    let client = ApiClient::default();
+
+    let small = client
+        .get_bytes(&format!("{}/small.png", server.uri()), MAX_EMOJI_BYTES)
+        .await
+        .unwrap();
+    let big = client
+        .get_bytes(&format!("{}/big.png", server.uri()), MAX_EMOJI_BYTES)
+        .await
+        .unwrap_err();
+
+    assert_eq!(small, b"\x89PNG");
+    assert_eq!(big, ApiError::TooLarge(MAX_EMOJI_BYTES));
+}
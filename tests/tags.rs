@@ -0,0 +1,174 @@
+use ronnbot_rs::customcommands::{self, CustomCommands};
+use ronnbot_rs::slashcommands::tag::{self, Caller, MAX_TAGS_PER_MEMBER};
+use serenity::model::channel::Attachment;
+
+const GUILD: u64 = 1;
+
+fn member(user_id: u64) -> Caller {
+    Caller {
+        guild_id: GUILD,
+        user_id,
+        moderator: false,
+    }
+}
+
+fn moderator() -> Caller {
+    Caller {
+        moderator: true,
+        ..member(99)
+    }
+}
+
+fn commands() -> CustomCommands {
+    customcommands::defaults()
+}
+
+#[test]
+fn created_tags_belong_to_the_guild_and_owner() {
+    let mut commands = commands();
+
+    let name = tag::create(&mut commands, &member(5), "WAVE", "o/".to_string(), None).unwrap();
+
+    assert_eq!(name, "wave");
+    let (key, created) = customcommands::find(&commands, Some(GUILD), "wave").unwrap();
+    assert_eq!(key, "1:wave");
+    assert_eq!(created.owner, Some(5));
+    assert!(customcommands::find(&commands, Some(2), "wave").is_none());
+}
+
+#[test]
+fn guild_tags_shadow_built_in_replies() {
+    let mut commands = commands();
+
+    tag::create(
+        &mut commands,
+        &member(5),
+        "gm",
+        "gm frens".to_string(),
+        None,
+    )
+    .unwrap();
+
+    let (_, found) = customcommands::find(&commands, Some(GUILD), "gm").unwrap();
+    assert_eq!(found.content.as_deref(), Some("gm frens"));
+    let (_, elsewhere) = customcommands::find(&commands, Some(2), "gm").unwrap();
+    assert_eq!(elsewhere.owner, None);
+}
+
+#[test]
+fn bad_names_and_duplicates_are_rejected() {
+    let mut commands = commands();
+
+    assert!(tag::create(&mut commands, &member(5), "has space", "x".into(), None).is_err());
+    for reserved in ["ping", "restart-shard", "reload", "help"] {
+        assert!(tag::create(&mut commands, &member(5), reserved, "x".into(), None).is_err());
+    }
+    assert!(tag::create(&mut commands, &member(5), "", "x".into(), None).is_err());
+    assert!(tag::create(&mut commands, &member(5), "x", "y".repeat(2001), None).is_err());
+    tag::create(&mut commands, &member(5), "dupe", "x".into(), None).unwrap();
+    assert!(tag::create(&mut commands, &member(6), "dupe", "x".into(), None).is_err());
+}
+
+#[test]
+fn members_are_limited_but_moderators_are_not() {
+    let mut commands = commands();
+    for i in 0..MAX_TAGS_PER_MEMBER {
+        tag::create(
+            &mut commands,
+            &member(5),
+            &format!("t{}", i),
+            "x".into(),
+            None,
+        )
+        .unwrap();
+    }
+
+    let error = tag::create(&mut commands, &member(5), "more", "x".into(), None).unwrap_err();
+
+    assert!(error.contains("already own"));
+    assert!(tag::create(&mut commands, &member(6), "more", "x".into(), None).is_ok());
+    assert!(tag::create(&mut commands, &moderator(), "mod", "x".into(), None).is_ok());
+}
+
+#[test]
+fn only_owners_and_moderators_can_change_tags() {
+    let mut commands = commands();
+    tag::create(&mut commands, &member(5), "mine", "x".into(), None).unwrap();
+
+    assert!(tag::edit(&mut commands, &member(6), "mine", Some("y".into()), None).is_err());
+    assert!(tag::delete(&mut commands, &member(6), "mine").is_err());
+    tag::edit(&mut commands, &member(5), "mine", Some("y".into()), None).unwrap();
+    assert_eq!(commands["1:mine"].content.as_deref(), Some("y"));
+    assert!(tag::delete(&mut commands, &moderator(), "mine").is_ok());
+    assert!(!commands.contains_key("1:mine"));
+}
+
+#[test]
+fn built_in_replies_cannot_be_changed_from_discord() {
+    let mut commands = commands();
+
+    let error = tag::delete(&mut commands, &moderator(), "izee").unwrap_err();
+
+    assert!(error.contains("built-in"));
+    assert!(commands.contains_key("izee"));
+}
+
+#[test]
+fn edits_return_replaced_uploads() {
+    let mut commands = commands();
+    tag::create(
+        &mut commands,
+        &member(5),
+        "pic",
+        "x".into(),
+        Some("old.png".into()),
+    )
+    .unwrap();
+
+    let removed = tag::edit(
+        &mut commands,
+        &member(5),
+        "pic",
+        None,
+        Some("new.png".into()),
+    )
+    .unwrap();
+
+    assert_eq!(removed, vec!["old.png".to_string()]);
+    assert_eq!(commands["1:pic"].uploads, vec!["new.png".to_string()]);
+}
+
+#[test]
+fn info_and_list_describe_tags() {
+    let mut commands = commands();
+    tag::create(&mut commands, &member(5), "wave", "o/".into(), None).unwrap();
+    commands.get_mut("1:wave").unwrap().uses = 3;
+
+    let info = tag::info(&commands, GUILD, "wave");
+    let list = tag::list(&commands, GUILD);
+
+    assert!(info.contains("<@5>"));
+    assert!(info.contains("Uses: 3"));
+    assert!(list.starts_with("Server tags: `wave`"));
+    assert!(list.contains("`izee`"));
+}
+
+#[test]
+fn uploads_with_the_same_name_do_not_collide() {
+    let attachment = |id: u64| -> Attachment {
+        serde_json::from_value(serde_json::json!({
+            "id": id.to_string(),
+            "filename": "my pic.png",
+            "size": 10,
+            "url": "https://cdn.discordapp.com/attachments/1/2/my_pic.png",
+            "proxy_url": "https://media.discordapp.net/attachments/1/2/my_pic.png",
+        }))
+        .unwrap()
+    };
+
+    let first = tag::upload_name(&member(5), "Wave", &attachment(10));
+    let second = tag::upload_name(&member(5), "Wave", &attachment(11));
+
+    assert_eq!(first, "1-wave-10-my_pic.png");
+    assert_ne!(first, second);
+}
@@ -0,0 +1,93 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ronnbot_rs::customcommands::template::{render, TemplateContext};
+use ronnbot_rs::customcommands::{self, CustomCommand};
+
+fn context(args: &[&str]) -> TemplateContext {
+    TemplateContext {
+        author_id: 42,
+        channel_id: 7,
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        count: 3,
+        ..Default::default()
+    }
+}
+
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(1)
+}
+
+#[test]
+fn simple_placeholders() {
+    let rendered = render(
+        "{user} in {channel} said {args} ({count})",
+        &context(&["hello", "there"]),
+        &mut rng(),
+    );
+
+    assert_eq!(rendered, "<@42> in <#7> said hello there (3)");
+}
+
+#[test]
+fn mentions_resolve_arguments_and_fixed_ids() {
+    let context = context(&["<@!555>", "bob", "123456789012345678"]);
+
+    assert_eq!(render("{mention:1}", &context, &mut rng()), "<@555>");
+    assert_eq!(render("{mention:2}", &context, &mut rng()), "bob");
+    assert_eq!(
+        render("{mention:3}", &context, &mut rng()),
+        "<@123456789012345678>"
+    );
+    assert_eq!(render("{mention:4}", &context, &mut rng()), "<@42>");
+    assert_eq!(
+        render("{mention:263925875374948352}", &context, &mut rng()),
+        "<@263925875374948352>"
+    );
+}
+
+#[test]
+fn random_picks_one_choice() {
+    let mut rng = rng();
+    for _ in 0..20 {
+        let rendered = render("GM {random:a|b|c}!", &context(&[]), &mut rng);
+        assert!(["GM a!", "GM b!", "GM c!"].contains(&rendered.as_str()));
+    }
+}
+
+#[test]
+fn unknown_and_unclosed_braces_are_kept() {
+    let rendered = render("{nope} {user} {open", &context(&[]), &mut rng());
+
+    assert_eq!(rendered, "{nope} <@42> {open");
+}
+
+#[test]
+fn pools_pick_content_and_images() {
+    let command = CustomCommand {
+        content: Some("unused".to_string()),
+        content_pool: vec!["gm {user}".to_string(), "gm fren".to_string()],
+        attachment_pool: vec!["a.png".to_string(), "b.png".to_string()],
+        ..Default::default()
+    };
+    let mut rng = rng();
+
+    for _ in 0..20 {
+        let (response, files) = command.render(&context(&[]), &mut rng);
+        let content = response.content.unwrap();
+        assert!(content == "gm <@42>" || content == "gm fren");
+        assert_eq!(files.len(), 1);
+        assert!(files[0] == "a.png" || files[0] == "b.png");
+    }
+}
+
+#[test]
+fn josh_mention_is_a_template() {
+    let josh = &customcommands::defaults()["josh"];
+
+    let (response, _) = josh.render(&context(&[]), &mut rng());
+
+    assert!(response
+        .content
+        .unwrap()
+        .starts_with("<@263925875374948352>https://"));
+}